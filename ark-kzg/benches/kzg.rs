@@ -0,0 +1,121 @@
+//! Setup, commit, open and check timings for degrees 2^8..2^20 on BLS12-381
+//! and BN254, and aggregated against individual verification of openings.
+//!
+//! Run with `cargo bench`, or restrict the largest degree with
+//! `KZG_BENCH_MAX_LOG_DEGREE=14 cargo bench` since setup at 2^20 is slow.
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_kzg::{Kzg, UniPoly};
+use ark_poly::{DenseUVPolynomial, Polynomial};
+use ark_serialize::CanonicalSerialize;
+use ark_std::{test_rng, UniformRand};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const LOG_DEGREES: [usize; 7] = [8, 10, 12, 14, 16, 18, 20];
+const NUM_OPENINGS: [usize; 3] = [4, 16, 64];
+
+fn log_degrees() -> impl Iterator<Item = usize> {
+    let max = std::env::var("KZG_BENCH_MAX_LOG_DEGREE")
+        .ok()
+        .and_then(|max| max.parse().ok())
+        .unwrap_or(20);
+    LOG_DEGREES.into_iter().filter(move |log| *log <= max)
+}
+
+fn bench_kzg<E: Pairing>(c: &mut Criterion, curve: &str) {
+    let rng = &mut test_rng();
+    let mut group = c.benchmark_group(format!("kzg/{}", curve));
+    group.sample_size(10);
+
+    for log_degree in log_degrees() {
+        let degree = (1 << log_degree) - 1;
+        group.bench_with_input(BenchmarkId::new("setup", log_degree), &degree, |b, d| {
+            b.iter(|| Kzg::<E>::setup(*d, &mut test_rng()).unwrap())
+        });
+
+        let pp = Kzg::<E>::setup(degree, rng).unwrap();
+        let (powers, vk) = Kzg::<E>::trim(&pp, degree).unwrap();
+        let f = UniPoly::<E>::rand(degree, rng);
+        let point = E::ScalarField::rand(rng);
+        let value = f.evaluate(&point);
+        let commitment = Kzg::<E>::commit(&powers, &f).unwrap();
+        let proof = Kzg::<E>::open(&powers, &f, point).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("commit", log_degree), &f, |b, f| {
+            b.iter(|| Kzg::<E>::commit(&powers, f).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("open", log_degree), &f, |b, f| {
+            b.iter(|| Kzg::<E>::open(&powers, f, point).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("check", log_degree), |b| {
+            b.iter(|| Kzg::<E>::verify(&vk, &commitment, point, value, &proof).unwrap())
+        });
+    }
+    group.finish();
+
+    // proofs are a single G1 point regardless of the degree
+    let pp = Kzg::<E>::setup(1, rng).unwrap();
+    let (powers, _) = Kzg::<E>::trim(&pp, 1).unwrap();
+    let f = UniPoly::<E>::rand(1, rng);
+    let commitment = Kzg::<E>::commit(&powers, &f).unwrap();
+    let proof = Kzg::<E>::open(&powers, &f, E::ScalarField::rand(rng)).unwrap();
+    println!(
+        "kzg/{}: commitment {} bytes, proof {} bytes",
+        curve,
+        commitment.compressed_size(),
+        proof.w.compressed_size()
+    );
+}
+
+fn bench_aggregate<E: Pairing>(c: &mut Criterion, curve: &str) {
+    let rng = &mut test_rng();
+    let mut group = c.benchmark_group(format!("aggregate/{}", curve));
+    group.sample_size(10);
+
+    let degree = 255;
+    let pp = Kzg::<E>::setup(degree, rng).unwrap();
+    let (powers, vk) = Kzg::<E>::trim(&pp, degree).unwrap();
+    for n in NUM_OPENINGS {
+        let mut commitments = vec![];
+        let mut points = vec![];
+        let mut values = vec![];
+        let mut proofs = vec![];
+        for _ in 0..n {
+            let f = UniPoly::<E>::rand(degree, rng);
+            let point = E::ScalarField::rand(rng);
+            commitments.push(Kzg::<E>::commit(&powers, &f).unwrap());
+            proofs.push(Kzg::<E>::open(&powers, &f, point).unwrap());
+            values.push(f.evaluate(&point));
+            points.push(point);
+        }
+
+        group.bench_function(BenchmarkId::new("individual", n), |b| {
+            b.iter(|| {
+                for i in 0..n {
+                    Kzg::<E>::verify(&vk, &commitments[i], points[i], values[i], &proofs[i])
+                        .unwrap();
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("aggregated", n), |b| {
+            b.iter(|| {
+                Kzg::<E>::verify_aggregate(&vk, &commitments, &points, &values, &proofs).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_bls12_381(c: &mut Criterion) {
+    bench_kzg::<Bls12_381>(c, "bls12_381");
+    bench_aggregate::<Bls12_381>(c, "bls12_381");
+}
+
+fn bench_bn254(c: &mut Criterion) {
+    bench_kzg::<Bn254>(c, "bn254");
+    bench_aggregate::<Bn254>(c, "bn254");
+}
+
+criterion_group!(benches, bench_bls12_381, bench_bn254);
+criterion_main!(benches);
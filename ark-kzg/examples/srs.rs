@@ -0,0 +1,41 @@
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use ark_bn254::Bn254;
+use ark_kzg::srs::read_ptau;
+use ark_kzg::{Error, Kzg, UniversalParams};
+use rand_core::OsRng;
+
+// Shares one SRS across runs and machines:
+//   cargo run --example srs -- params.srs 1024   generates and writes fresh parameters
+//   cargo run --example srs -- params.srs        loads and validates them
+//   cargo run --example srs -- pot.ptau 1024     imports a snarkjs BN254 transcript
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+    let path = Path::new(args.get(1).map_or("params.srs", String::as_str));
+    let max_degree = args.get(2).map(|d| d.parse().expect("degree is a number"));
+
+    let pp = match (path.extension().and_then(|e| e.to_str()), max_degree) {
+        (Some("ptau"), Some(max_degree)) => {
+            let mut reader = BufReader::new(File::open(path)?);
+            let pp = read_ptau::<Bn254, _, _, _>(&mut reader, max_degree)?;
+            pp.write_to_file(path.with_extension("srs"))?;
+            pp
+        }
+        (_, Some(max_degree)) => {
+            let pp = Kzg::<Bn254>::setup(max_degree, &mut OsRng)?;
+            pp.write_to_file(path)?;
+            pp
+        }
+        (_, None) => UniversalParams::<Bn254>::read_from_file(path)?,
+    };
+
+    println!(
+        "SRS with {} G1 and {} G2 powers is consistent",
+        pp.powers_of_g.len(),
+        pp.powers_of_h.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,425 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, Polynomial};
+
+use crate::kzg10::{divide_by_linear, Commitment, Kzg, Powers, Proof, UniPoly, VerifierKey};
+use crate::transcript::Transcript;
+use crate::Error;
+
+impl<E: Pairing> Kzg<E> {
+    /// Opens several polynomials at the same `point` with a single proof for the
+    /// random linear combination `Σ γ^i f_i`. `gamma` has to be sampled by the
+    /// verifier once the commitments are fixed.
+    pub fn open_batch(
+        powers: &Powers<E>,
+        polynomials: &[UniPoly<E>],
+        point: E::ScalarField,
+        gamma: E::ScalarField,
+    ) -> Result<Proof<E>, Error> {
+        for polynomial in polynomials {
+            Self::check_degree(polynomial, powers)?;
+        }
+        let combined = linear_combination(polynomials, gamma);
+        Self::open(powers, &combined, point)
+    }
+
+    /// Checks a proof produced by [`Kzg::open_batch`]: folds commitments and
+    /// values with the powers of `gamma` and runs a single opening check
+    pub fn verify_batch(
+        vk: &VerifierKey<E>,
+        commitments: &[Commitment<E>],
+        point: E::ScalarField,
+        values: &[E::ScalarField],
+        gamma: E::ScalarField,
+        proof: &Proof<E>,
+    ) -> Result<(), Error> {
+        check_lengths(commitments.len(), values.len())?;
+        let powers_of_gamma = powers_of(gamma, commitments.len());
+        let bases: Vec<E::G1Affine> = commitments.iter().map(|c| c.0).collect();
+        let commitment = E::G1::msm_unchecked(&bases, &powers_of_gamma);
+        let value = values
+            .iter()
+            .zip(&powers_of_gamma)
+            .map(|(value, power)| *value * power)
+            .sum();
+
+        Self::verify(
+            vk,
+            &Commitment(commitment.into_affine()),
+            point,
+            value,
+            proof,
+        )
+    }
+
+    /// Opens `polynomial` at all `points` with the commitment to the quotient
+    /// `(f(X) - I(X)) / Z(X)`, where `Z` vanishes on the points and `I`
+    /// interpolates the evaluations of `f` on them
+    pub fn open_multi_point(
+        powers: &Powers<E>,
+        polynomial: &UniPoly<E>,
+        points: &[E::ScalarField],
+    ) -> Result<Proof<E>, Error> {
+        Self::check_degree(polynomial, powers)?;
+        let values: Vec<_> = points.iter().map(|z| polynomial.evaluate(z)).collect();
+        let interpolant = interpolate(points, &values)?;
+        let vanishing = vanishing_polynomial(points);
+
+        let quotient = &(polynomial - &interpolant) / &vanishing;
+        let w = E::G1::msm_unchecked(&powers.powers_of_g, &quotient.coeffs);
+        Ok(Proof {
+            w: w.into_affine(),
+            random_v: None,
+        })
+    }
+
+    /// Checks a proof produced by [`Kzg::open_multi_point`] with the single
+    /// pairing product `e(C, H) · e(-W, [Z(τ)]G2) · e(-G, [I(τ)]G2) = 1`
+    pub fn verify_multi_point(
+        vk: &VerifierKey<E>,
+        commitment: &Commitment<E>,
+        points: &[E::ScalarField],
+        values: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> Result<(), Error> {
+        check_lengths(points.len(), values.len())?;
+        let max_points = vk
+            .powers_of_h
+            .len()
+            .checked_sub(1)
+            .ok_or(Error::InvalidSrs("the verifier key has no G2 powers"))?;
+        if points.len() > max_points {
+            return Err(Error::TooManyPoints {
+                num_points: points.len(),
+                max_points,
+            });
+        }
+
+        let interpolant = interpolate(points, values)?;
+        let vanishing = vanishing_polynomial(points);
+        let vanishing_h = E::G2::msm_unchecked(&vk.powers_of_h, &vanishing.coeffs);
+        let interpolant_h = E::G2::msm_unchecked(&vk.powers_of_h, &interpolant.coeffs);
+
+        let check = E::multi_pairing(
+            [
+                commitment.0.into_group(),
+                -proof.w.into_group(),
+                -vk.g.into_group(),
+            ],
+            [vk.h()?.into_group(), vanishing_h, interpolant_h],
+        );
+        match check.is_zero() {
+            true => Ok(()),
+            false => Err(Error::InvalidProof),
+        }
+    }
+
+    /// Checks independent openings `(C_i, z_i, v_i, W_i)` with a single
+    /// pairing product. Each check `e(C_i - [v_i]G1, H) = e(W_i, [τ]H - [z_i]H)`
+    /// is rearranged to `e(C_i - [v_i]G1 + [z_i]W_i, H) = e(W_i, [τ]H)` and the
+    /// checks are combined with powers of a challenge `r` derived from all of
+    /// them, so that a single invalid opening makes the product fail.
+    pub fn verify_aggregate(
+        vk: &VerifierKey<E>,
+        commitments: &[Commitment<E>],
+        points: &[E::ScalarField],
+        values: &[E::ScalarField],
+        proofs: &[Proof<E>],
+    ) -> Result<(), Error> {
+        check_lengths(commitments.len(), points.len())?;
+        check_lengths(commitments.len(), values.len())?;
+        check_lengths(commitments.len(), proofs.len())?;
+
+        let mut transcript = Transcript::new(b"kzg-aggregate");
+        transcript.append(b"commitments", commitments);
+        transcript.append(b"points", points);
+        transcript.append(b"values", values);
+        transcript.append(b"proofs", proofs);
+        let r: E::ScalarField = transcript.challenge(b"r");
+        let powers_of_r = powers_of(r, commitments.len());
+
+        let mut bases = Vec::with_capacity(2 * commitments.len());
+        let mut scalars = Vec::with_capacity(2 * commitments.len());
+        let mut value = E::ScalarField::zero();
+        let mut random_v = E::ScalarField::zero();
+        for (i, power) in powers_of_r.iter().enumerate() {
+            bases.extend([commitments[i].0, proofs[i].w]);
+            scalars.extend([*power, *power * points[i]]);
+            value += *power * values[i];
+            if let Some(v) = proofs[i].random_v {
+                random_v += *power * v;
+            }
+        }
+
+        let mut lhs = E::G1::msm_unchecked(&bases, &scalars) - vk.g * value;
+        if proofs.iter().any(|proof| proof.random_v.is_some()) {
+            let gamma_g = vk.gamma_g.ok_or(Error::HidingUnsupported)?;
+            lhs -= gamma_g * random_v;
+        }
+        let witnesses: Vec<E::G1Affine> = proofs.iter().map(|proof| proof.w).collect();
+        let rhs = E::G1::msm_unchecked(&witnesses, &powers_of_r);
+
+        let check = E::multi_pairing([lhs, -rhs], [vk.h()?, vk.beta_h()?]);
+        match check.is_zero() {
+            true => Ok(()),
+            false => Err(Error::InvalidProof),
+        }
+    }
+}
+
+/// `Z(X) = Π (X - z_i)`
+pub fn vanishing_polynomial<F: Field>(points: &[F]) -> DensePolynomial<F> {
+    let mut coeffs = vec![F::one()];
+    for point in points {
+        // multiply by (X - z): shift up by one and subtract z times the old coefficients
+        coeffs.insert(0, F::zero());
+        for i in 0..coeffs.len() - 1 {
+            let next = coeffs[i + 1];
+            coeffs[i] -= *point * next;
+        }
+    }
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+/// Lagrange interpolation of the polynomial of degree `< n` through `(z_i, v_i)`
+pub fn interpolate<F: Field>(points: &[F], values: &[F]) -> Result<DensePolynomial<F>, Error> {
+    check_lengths(points.len(), values.len())?;
+    let vanishing = vanishing_polynomial(points);
+
+    let mut coeffs = vec![F::zero(); points.len()];
+    for (point, value) in points.iter().zip(values) {
+        let basis = divide_by_linear(&vanishing, *point);
+        let denominator = basis.evaluate(point);
+        let scale = denominator.inverse().ok_or(Error::DuplicatePoints)? * value;
+        for (coeff, basis_coeff) in coeffs.iter_mut().zip(&basis.coeffs) {
+            *coeff += scale * basis_coeff;
+        }
+    }
+    Ok(DensePolynomial::from_coefficients_vec(coeffs))
+}
+
+/// `Σ γ^i f_i`
+pub fn linear_combination<F: Field>(
+    polynomials: &[DensePolynomial<F>],
+    gamma: F,
+) -> DensePolynomial<F> {
+    let mut combined = DensePolynomial::zero();
+    for (polynomial, power) in polynomials.iter().zip(powers_of(gamma, polynomials.len())) {
+        combined += (power, polynomial);
+    }
+    combined
+}
+
+/// `[1, x, x^2, ..., x^(n-1)]`
+pub fn powers_of<F: Field>(x: F, n: usize) -> Vec<F> {
+    ark_std::iter::successors(Some(F::one()), |power| Some(*power * x))
+        .take(n)
+        .collect()
+}
+
+fn check_lengths(expected: usize, found: usize) -> Result<(), Error> {
+    match expected == found {
+        true => Ok(()),
+        false => Err(Error::MismatchedLengths { expected, found }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::{test_rng, UniformRand};
+
+    type Kzg381 = Kzg<Bls12_381>;
+
+    #[test]
+    fn test_vanishing_and_interpolation() {
+        let rng = &mut test_rng();
+        let f = UniPoly::<Bls12_381>::rand(4, rng);
+        let points: Vec<Fr> = (0..5).map(|_| Fr::rand(rng)).collect();
+        let values: Vec<Fr> = points.iter().map(|z| f.evaluate(z)).collect();
+
+        let vanishing = vanishing_polynomial(&points);
+        assert_eq!(vanishing.degree(), 5);
+        assert!(points.iter().all(|z| vanishing.evaluate(z).is_zero()));
+        assert_eq!(interpolate(&points, &values).unwrap(), f);
+    }
+
+    #[test]
+    fn test_interpolate_duplicate_points() {
+        let points = [Fr::from(1u64), Fr::from(2u64), Fr::from(1u64)];
+        let values = [Fr::from(3u64); 3];
+        assert!(matches!(
+            interpolate(&points, &values),
+            Err(Error::DuplicatePoints)
+        ));
+    }
+
+    #[test]
+    fn test_batch_many_polynomials_one_point() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(16, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 16).unwrap();
+
+        let polynomials: Vec<_> = [16, 3, 9]
+            .iter()
+            .map(|d| UniPoly::<Bls12_381>::rand(*d, rng))
+            .collect();
+        let commitments: Vec<_> = polynomials
+            .iter()
+            .map(|f| Kzg381::commit(&powers, f).unwrap())
+            .collect();
+        let point = Fr::rand(rng);
+        let mut values: Vec<_> = polynomials.iter().map(|f| f.evaluate(&point)).collect();
+
+        let gamma = Fr::rand(rng);
+        let proof = Kzg381::open_batch(&powers, &polynomials, point, gamma).unwrap();
+        assert!(Kzg381::verify_batch(&vk, &commitments, point, &values, gamma, &proof).is_ok());
+
+        // a different challenge does not verify
+        let other_gamma = gamma + Fr::ONE;
+        assert!(
+            Kzg381::verify_batch(&vk, &commitments, point, &values, other_gamma, &proof).is_err()
+        );
+
+        // tampering with a single evaluation is caught
+        values[1] += Fr::ONE;
+        assert!(matches!(
+            Kzg381::verify_batch(&vk, &commitments, point, &values, gamma, &proof),
+            Err(Error::InvalidProof)
+        ));
+    }
+
+    #[test]
+    fn test_batch_one_polynomial_many_points() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(16, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 16).unwrap();
+
+        let f = UniPoly::<Bls12_381>::rand(16, rng);
+        let commitment = Kzg381::commit(&powers, &f).unwrap();
+        let points: Vec<Fr> = (0..4).map(|_| Fr::rand(rng)).collect();
+        let mut values: Vec<_> = points.iter().map(|z| f.evaluate(z)).collect();
+
+        let proof = Kzg381::open_multi_point(&powers, &f, &points).unwrap();
+        assert!(Kzg381::verify_multi_point(&vk, &commitment, &points, &values, &proof).is_ok());
+
+        values[2] += Fr::ONE;
+        assert!(matches!(
+            Kzg381::verify_multi_point(&vk, &commitment, &points, &values, &proof),
+            Err(Error::InvalidProof)
+        ));
+    }
+
+    #[test]
+    fn test_multi_point_proof_is_bound_to_points() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(8, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 8).unwrap();
+
+        let f = UniPoly::<Bls12_381>::rand(8, rng);
+        let commitment = Kzg381::commit(&powers, &f).unwrap();
+        let mut points: Vec<Fr> = (0..3).map(|_| Fr::rand(rng)).collect();
+        let values: Vec<_> = points.iter().map(|z| f.evaluate(z)).collect();
+        let proof = Kzg381::open_multi_point(&powers, &f, &points).unwrap();
+
+        points[0] += Fr::ONE;
+        assert!(Kzg381::verify_multi_point(&vk, &commitment, &points, &values, &proof).is_err());
+    }
+
+    #[test]
+    fn test_too_many_points() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(4, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 2).unwrap();
+
+        let f = UniPoly::<Bls12_381>::rand(2, rng);
+        let commitment = Kzg381::commit(&powers, &f).unwrap();
+        let points: Vec<Fr> = (0..3).map(|_| Fr::rand(rng)).collect();
+        let values: Vec<_> = points.iter().map(|z| f.evaluate(z)).collect();
+        let proof = Kzg381::open_multi_point(&powers, &f, &points).unwrap();
+
+        assert!(matches!(
+            Kzg381::verify_multi_point(&vk, &commitment, &points, &values, &proof),
+            Err(Error::TooManyPoints {
+                num_points: 3,
+                max_points: 2
+            })
+        ));
+
+        let vk = VerifierKey {
+            powers_of_h: vec![],
+            ..vk
+        };
+        assert!(matches!(
+            Kzg381::verify_multi_point(&vk, &commitment, &points, &values, &proof),
+            Err(Error::InvalidSrs(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_aggregate() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(8, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 8).unwrap();
+
+        let mut commitments = vec![];
+        let mut points = vec![];
+        let mut values = vec![];
+        let mut proofs = vec![];
+        for i in 0..6 {
+            let f = UniPoly::<Bls12_381>::rand(8, rng);
+            let point = Fr::rand(rng);
+            // mix plain and hiding openings
+            let (commitment, proof) = match i % 2 {
+                0 => (
+                    Kzg381::commit(&powers, &f).unwrap(),
+                    Kzg381::open(&powers, &f, point).unwrap(),
+                ),
+                _ => {
+                    let (commitment, randomness) =
+                        Kzg381::commit_hiding(&powers, &f, 1, rng).unwrap();
+                    let proof = Kzg381::open_hiding(&powers, &f, point, &randomness).unwrap();
+                    (commitment, proof)
+                }
+            };
+            commitments.push(commitment);
+            points.push(point);
+            values.push(f.evaluate(&point));
+            proofs.push(proof);
+        }
+        assert!(Kzg381::verify_aggregate(&vk, &commitments, &points, &values, &proofs).is_ok());
+
+        // swapping two proofs breaks the product even though each proof is valid elsewhere
+        proofs.swap(0, 2);
+        assert!(matches!(
+            Kzg381::verify_aggregate(&vk, &commitments, &points, &values, &proofs),
+            Err(Error::InvalidProof)
+        ));
+        proofs.swap(0, 2);
+
+        values[3] += Fr::ONE;
+        assert!(matches!(
+            Kzg381::verify_aggregate(&vk, &commitments, &points, &values, &proofs),
+            Err(Error::InvalidProof)
+        ));
+        assert!(matches!(
+            Kzg381::verify_aggregate(&vk, &commitments, &points[1..], &values, &proofs),
+            Err(Error::MismatchedLengths {
+                expected: 6,
+                found: 5
+            })
+        ));
+
+        let vk = VerifierKey {
+            powers_of_h: vk.powers_of_h[..1].to_vec(),
+            ..vk
+        };
+        assert!(matches!(
+            Kzg381::verify_aggregate(&vk, &commitments, &points, &values, &proofs),
+            Err(Error::InvalidSrs(_))
+        ));
+    }
+}
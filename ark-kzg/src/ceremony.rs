@@ -0,0 +1,317 @@
+//! Local simulation of a multi-party powers-of-tau ceremony. Starting from
+//! `τ = 1`, every participant multiplies the current `τ` by a secret `s` and
+//! publishes `[s]G1`, `[s]G2`, a Schnorr proof of knowledge of `s` and the new
+//! `[τ]G1`. As long as one participant discards their secret, nobody knows
+//! the final `τ`.
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand_core::RngCore;
+
+use crate::batch::powers_of;
+use crate::kzg10::UniversalParams;
+use crate::transcript::Transcript;
+use crate::Error;
+
+/// Schnorr proof of knowledge of `s` for `[s]G1`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KnowledgeProof<E: Pairing> {
+    pub commitment: E::G1Affine,
+    pub response: E::ScalarField,
+}
+
+/// Public record of one update `τ_i = τ_(i-1) · s_i`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution<E: Pairing> {
+    /// `[s_i]G1`
+    pub pubkey_g1: E::G1Affine,
+    /// `[s_i]G2`
+    pub pubkey_g2: E::G2Affine,
+    pub proof: KnowledgeProof<E>,
+    /// `[τ_i]G1` after this contribution
+    pub running_product: E::G1Affine,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Ceremony<E: Pairing> {
+    params: UniversalParams<E>,
+    contributions: Vec<Contribution<E>>,
+}
+
+impl<E: Pairing> Ceremony<E> {
+    /// Starts a ceremony for polynomials up to `max_degree` with `τ = 1`
+    pub fn new(max_degree: usize) -> Result<Self, Error> {
+        if max_degree < 1 {
+            return Err(Error::DegreeIsZero);
+        }
+        // the ceremony only produces powers of τ, so commitments cannot be hiding
+        let params = UniversalParams {
+            powers_of_g: vec![E::G1Affine::generator(); max_degree + 1],
+            powers_of_gamma_g: vec![],
+            powers_of_h: vec![E::G2Affine::generator(); max_degree + 1],
+        };
+        Ok(Ceremony {
+            params,
+            contributions: vec![],
+        })
+    }
+
+    /// Updates the parameters with a fresh secret that is dropped afterwards
+    pub fn contribute<R: RngCore>(&mut self, rng: &mut R) -> Result<&Contribution<E>, Error> {
+        let secret = E::ScalarField::rand(rng);
+        let nonce = E::ScalarField::rand(rng);
+        self.contribute_with_secret(secret, nonce)
+    }
+
+    /// Updates the parameters with `secret`, using `nonce` for the knowledge proof
+    pub fn contribute_with_secret(
+        &mut self,
+        secret: E::ScalarField,
+        nonce: E::ScalarField,
+    ) -> Result<&Contribution<E>, Error> {
+        if secret.is_zero() {
+            return Err(invalid(self.contributions.len(), "secret must be non-zero"));
+        }
+
+        let powers_of_secret = powers_of(secret, self.params.powers_of_g.len());
+        let powers_of_g: Vec<E::G1> = self
+            .params
+            .powers_of_g
+            .iter()
+            .zip(&powers_of_secret)
+            .map(|(power, s)| *power * s)
+            .collect();
+        let powers_of_h: Vec<E::G2> = self
+            .params
+            .powers_of_h
+            .iter()
+            .zip(&powers_of_secret)
+            .map(|(power, s)| *power * s)
+            .collect();
+        self.params = UniversalParams {
+            powers_of_g: E::G1::normalize_batch(&powers_of_g),
+            powers_of_gamma_g: vec![],
+            powers_of_h: E::G2::normalize_batch(&powers_of_h),
+        };
+
+        let index = self.contributions.len();
+        let pubkey_g1 = (E::G1Affine::generator() * secret).into_affine();
+        let commitment = (E::G1Affine::generator() * nonce).into_affine();
+        let challenge = knowledge_challenge::<E>(index, &pubkey_g1, &commitment);
+        self.contributions.push(Contribution {
+            pubkey_g1,
+            pubkey_g2: (E::G2Affine::generator() * secret).into_affine(),
+            proof: KnowledgeProof {
+                commitment,
+                response: nonce + challenge * secret,
+            },
+            running_product: self.params.powers_of_g[1],
+        });
+        Ok(&self.contributions[index])
+    }
+
+    /// Verifies every contribution of the chain and that the final parameters
+    /// are consistent powers of the final running product. A ceremony without
+    /// contributions is rejected, its `τ = 1` is known to everyone.
+    pub fn verify(&self) -> Result<(), Error> {
+        let g = E::G1Affine::generator();
+        let h = E::G2Affine::generator();
+
+        if self.contributions.is_empty() {
+            return Err(Error::InvalidSrs("the ceremony has no contributions"));
+        }
+        // deserialized ceremonies may carry too few powers
+        if self.params.powers_of_g.len() < 2 || self.params.powers_of_h.len() < 2 {
+            return Err(Error::InvalidSrs("at least two powers are required"));
+        }
+
+        let mut previous = g;
+        for (index, contribution) in self.contributions.iter().enumerate() {
+            if contribution.pubkey_g1.is_zero() {
+                return Err(invalid(index, "secret must be non-zero"));
+            }
+            // the same secret is used in both groups
+            if E::pairing(contribution.pubkey_g1, h) != E::pairing(g, contribution.pubkey_g2) {
+                return Err(invalid(index, "G1 and G2 public keys differ"));
+            }
+            let proof = &contribution.proof;
+            let challenge =
+                knowledge_challenge::<E>(index, &contribution.pubkey_g1, &proof.commitment);
+            if g * proof.response != proof.commitment + contribution.pubkey_g1 * challenge {
+                return Err(invalid(index, "invalid proof of knowledge"));
+            }
+            // the update builds on the previous contribution
+            if E::pairing(contribution.running_product, h)
+                != E::pairing(previous, contribution.pubkey_g2)
+            {
+                return Err(invalid(index, "update does not extend the previous τ"));
+            }
+            previous = contribution.running_product;
+        }
+
+        if self.params.powers_of_g[0] != g || self.params.powers_of_h[0] != h {
+            return Err(Error::InvalidSrs(
+                "parameters do not start at the generators",
+            ));
+        }
+        if self.params.powers_of_g[1] != previous {
+            return Err(Error::InvalidSrs(
+                "parameters do not match the last contribution",
+            ));
+        }
+        self.params.check_consistency()
+    }
+
+    pub fn contributions(&self) -> &[Contribution<E>] {
+        &self.contributions
+    }
+
+    pub fn params(&self) -> &UniversalParams<E> {
+        &self.params
+    }
+
+    pub fn into_params(self) -> UniversalParams<E> {
+        self.params
+    }
+}
+
+fn invalid(index: usize, reason: &'static str) -> Error {
+    Error::InvalidContribution { index, reason }
+}
+
+fn knowledge_challenge<E: Pairing>(
+    index: usize,
+    pubkey: &E::G1Affine,
+    commitment: &E::G1Affine,
+) -> E::ScalarField {
+    let mut transcript = Transcript::new(b"powers-of-tau-contribution");
+    transcript.append(b"index", &(index as u64));
+    transcript.append(b"pubkey", pubkey);
+    transcript.append(b"commitment", commitment);
+    transcript.challenge(b"challenge")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kzg, UniPoly};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_poly::{DenseUVPolynomial, Polynomial};
+    use ark_std::test_rng;
+
+    type Kzg381 = Kzg<Bls12_381>;
+
+    fn ceremony(participants: usize) -> Ceremony<Bls12_381> {
+        let rng = &mut test_rng();
+        let mut ceremony = Ceremony::new(16).unwrap();
+        for _ in 0..participants {
+            ceremony.contribute(rng).unwrap();
+        }
+        ceremony
+    }
+
+    #[test]
+    fn test_ceremony_matches_product_of_secrets() {
+        let secrets = [Fr::from(3u64), Fr::from(5u64), Fr::from(7u64)];
+        let mut ceremony = Ceremony::<Bls12_381>::new(8).unwrap();
+        for secret in secrets {
+            ceremony
+                .contribute_with_secret(secret, Fr::from(11u64))
+                .unwrap();
+        }
+        assert!(ceremony.verify().is_ok());
+
+        let expected = Kzg381::setup_from_tau(8, Fr::from(105u64)).unwrap();
+        assert_eq!(ceremony.params(), &expected);
+    }
+
+    #[test]
+    fn test_ceremony_srs_commits_and_opens() {
+        let ceremony = ceremony(4);
+        assert!(ceremony.verify().is_ok());
+
+        let rng = &mut test_rng();
+        let (powers, vk) = Kzg381::trim(ceremony.params(), 16).unwrap();
+        let f = UniPoly::<Bls12_381>::rand(16, rng);
+        let comm = Kzg381::commit(&powers, &f).unwrap();
+        let point = Fr::rand(rng);
+        let proof = Kzg381::open(&powers, &f, point).unwrap();
+        assert!(Kzg381::verify(&vk, &comm, point, f.evaluate(&point), &proof).is_ok());
+    }
+
+    #[test]
+    fn test_zero_secret_is_rejected() {
+        let mut ceremony = Ceremony::<Bls12_381>::new(4).unwrap();
+        assert!(ceremony
+            .contribute_with_secret(Fr::zero(), Fr::from(1u64))
+            .is_err());
+    }
+
+    #[test]
+    fn test_replaced_params_are_rejected() {
+        // a participant discarding the previous τ and starting over from their own secret
+        let mut ceremony = ceremony(2);
+        let mut fresh = Ceremony::<Bls12_381>::new(16).unwrap();
+        let contribution = *fresh
+            .contribute_with_secret(Fr::from(42u64), Fr::from(1u64))
+            .unwrap();
+        ceremony.params = fresh.params;
+        ceremony.contributions.push(contribution);
+
+        assert!(matches!(
+            ceremony.verify(),
+            Err(Error::InvalidContribution { index: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_forged_knowledge_proof_is_rejected() {
+        let mut ceremony = ceremony(3);
+        ceremony.contributions[1].proof.response += Fr::from(1u64);
+        assert!(matches!(
+            ceremony.verify(),
+            Err(Error::InvalidContribution {
+                index: 1,
+                reason: "invalid proof of knowledge"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_mismatched_pubkeys_are_rejected() {
+        let mut ceremony = ceremony(2);
+        ceremony.contributions[0].pubkey_g2 = ceremony.contributions[1].pubkey_g2;
+        assert!(matches!(
+            ceremony.verify(),
+            Err(Error::InvalidContribution { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_tampered_final_params_are_rejected() {
+        let mut ceremony = ceremony(2);
+        ceremony.params.powers_of_g[7] = ceremony.params.powers_of_g[6];
+        assert!(ceremony.verify().is_err());
+    }
+
+    #[test]
+    fn test_ceremony_without_contributions_is_rejected() {
+        let ceremony = Ceremony::<Bls12_381>::new(4).unwrap();
+        assert!(matches!(
+            ceremony.verify(),
+            Err(Error::InvalidSrs("the ceremony has no contributions"))
+        ));
+    }
+
+    #[test]
+    fn test_truncated_params_are_rejected() {
+        let mut short_g1 = ceremony(1);
+        short_g1.params.powers_of_g.truncate(1);
+        assert!(matches!(short_g1.verify(), Err(Error::InvalidSrs(_))));
+
+        let mut no_g2 = ceremony(1);
+        no_g2.params.powers_of_h.clear();
+        assert!(matches!(no_g2.verify(), Err(Error::InvalidSrs(_))));
+    }
+}
@@ -0,0 +1,730 @@
+//! EIP-4844 blob commitments on BLS12-381, following the polynomial commitment
+//! functions of the Deneb consensus specs and the point evaluation precompile.
+//!
+//! A blob holds 4096 big-endian field elements, the evaluations of a polynomial
+//! over the 4096-th roots of unity in bit-reversed order. Commitments and
+//! quotients are computed directly from evaluations with the Lagrange-form G1
+//! points of the trusted setup, and all points use the compressed ZCash
+//! encoding that arkworks implements for BLS12-381.
+use std::fs;
+use std::path::Path;
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::scalar_mul::ScalarMul;
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, PrimeField};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha2::{Digest, Sha256};
+
+use crate::kzg10::{Commitment, Kzg, Proof, VerifierKey};
+use crate::lagrange::{evaluate_in_evaluation_form, quotient_in_evaluation_form};
+use crate::Error;
+
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT;
+pub const BYTES_PER_COMMITMENT: usize = 48;
+pub const BYTES_PER_PROOF: usize = 48;
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+const FIAT_SHAMIR_PROTOCOL_DOMAIN: &[u8] = b"FSBLOBVERIFY_V1_";
+const POINT_EVALUATION_INPUT_LENGTH: usize = 192;
+
+/// The trusted setup of the KZG ceremony: the Lagrange-form G1 points, stored
+/// in bit-reversed order to match the blob layout, and `[1]G2, [τ]G2`.
+pub struct TrustedSetup {
+    g1_lagrange_brp: Vec<G1Affine>,
+    roots_of_unity_brp: Vec<Fr>,
+    vk: VerifierKey<Bls12_381>,
+}
+
+impl TrustedSetup {
+    /// Creates the setup from `[L_i(τ)]G1` for the Lagrange polynomials of the
+    /// roots of unity in natural order, and the G2 powers `[τ^i]G2`.
+    pub fn new(g1_lagrange: Vec<G1Affine>, g2_monomial: Vec<G2Affine>) -> Result<Self, Error> {
+        if g1_lagrange.len() != FIELD_ELEMENTS_PER_BLOB {
+            return Err(Error::MismatchedLengths {
+                expected: FIELD_ELEMENTS_PER_BLOB,
+                found: g1_lagrange.len(),
+            });
+        }
+        if g2_monomial.len() < 2 {
+            return Err(Error::InvalidSrs("at least two G2 powers are required"));
+        }
+        if g2_monomial[0] != G2Affine::generator() {
+            return Err(Error::InvalidSrs("first G2 power must be the generator"));
+        }
+
+        let domain = Radix2EvaluationDomain::<Fr>::new(FIELD_ELEMENTS_PER_BLOB)
+            .expect("BLS12-381 has a subgroup of order 4096");
+        let roots_of_unity: Vec<Fr> = domain.elements().collect();
+        Ok(TrustedSetup {
+            g1_lagrange_brp: bit_reversal_permutation(&g1_lagrange),
+            roots_of_unity_brp: bit_reversal_permutation(&roots_of_unity),
+            vk: VerifierKey {
+                g: G1Affine::generator(),
+                gamma_g: None,
+                powers_of_h: g2_monomial[..2].to_vec(),
+            },
+        })
+    }
+
+    /// Loads the `trusted_setup.txt` file shipped with c-kzg-4844: the number
+    /// of G1 and G2 points, followed by one hex encoded point per line, first
+    /// the Lagrange-form G1 points and then the monomial G2 points. Trailing
+    /// monomial G1 points are ignored.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let contents = fs::read_to_string(path)?;
+        let mut tokens = contents.split_whitespace();
+        let mut next = || {
+            tokens
+                .next()
+                .ok_or(Error::InvalidSrs("trusted setup is truncated"))
+        };
+        let mut count = || -> Result<usize, Error> {
+            next()?
+                .parse()
+                .map_err(|_| Error::InvalidSrs("invalid number of points"))
+        };
+        let (num_g1, num_g2) = (count()?, count()?);
+
+        let g1_lagrange = (0..num_g1)
+            .map(|_| Ok(G1Affine::deserialize_compressed(&decode_hex(next()?)?[..])?))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let g2_monomial = (0..num_g2)
+            .map(|_| Ok(G2Affine::deserialize_compressed(&decode_hex(next()?)?[..])?))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Self::new(g1_lagrange, g2_monomial)
+    }
+
+    /// Derives a setup from a known `τ`. Anybody knowing `τ` can forge proofs,
+    /// so this is only meant for tests.
+    pub fn insecure_from_tau(tau: Fr) -> Self {
+        let domain = Radix2EvaluationDomain::<Fr>::new(FIELD_ELEMENTS_PER_BLOB)
+            .expect("BLS12-381 has a subgroup of order 4096");
+        let lagrange_coefficients = domain.evaluate_all_lagrange_coefficients(tau);
+        let g1_lagrange = G1Projective::generator().batch_mul(&lagrange_coefficients);
+        let g2_monomial = vec![
+            G2Affine::generator(),
+            (G2Affine::generator() * tau).into_affine(),
+        ];
+        Self::new(g1_lagrange, g2_monomial).expect("setup has the right number of points")
+    }
+
+    pub fn verifier_key(&self) -> &VerifierKey<Bls12_381> {
+        &self.vk
+    }
+
+    pub fn blob_to_kzg_commitment(&self, blob: &[u8]) -> Result<Commitment<Bls12_381>, Error> {
+        let polynomial = blob_to_polynomial(blob)?;
+        Ok(Commitment(self.g1_lincomb(&polynomial)))
+    }
+
+    /// Proves the evaluation of the blob polynomial at `z`, returning the proof
+    /// and the evaluation `y`.
+    pub fn compute_kzg_proof(&self, blob: &[u8], z: Fr) -> Result<(Proof<Bls12_381>, Fr), Error> {
+        let polynomial = blob_to_polynomial(blob)?;
+        Ok(self.compute_kzg_proof_impl(&polynomial, z))
+    }
+
+    /// Proves the evaluation at the Fiat-Shamir challenge derived from the blob
+    /// and its commitment.
+    pub fn compute_blob_kzg_proof(
+        &self,
+        blob: &[u8],
+        commitment: &Commitment<Bls12_381>,
+    ) -> Result<Proof<Bls12_381>, Error> {
+        let polynomial = blob_to_polynomial(blob)?;
+        let challenge = compute_challenge(blob, commitment);
+        Ok(self.compute_kzg_proof_impl(&polynomial, challenge).0)
+    }
+
+    pub fn verify_kzg_proof(
+        &self,
+        commitment: &Commitment<Bls12_381>,
+        z: Fr,
+        y: Fr,
+        proof: &Proof<Bls12_381>,
+    ) -> Result<(), Error> {
+        if proof.random_v.is_some() {
+            return Err(Error::InvalidProof);
+        }
+        Kzg::verify(&self.vk, commitment, z, y, proof)
+    }
+
+    pub fn verify_blob_kzg_proof(
+        &self,
+        blob: &[u8],
+        commitment: &Commitment<Bls12_381>,
+        proof: &Proof<Bls12_381>,
+    ) -> Result<(), Error> {
+        let polynomial = blob_to_polynomial(blob)?;
+        let challenge = compute_challenge(blob, commitment);
+        let y = self.evaluate_polynomial_in_evaluation_form(&polynomial, challenge);
+        self.verify_kzg_proof(commitment, challenge, y, proof)
+    }
+
+    pub fn verify_blob_kzg_proof_batch(
+        &self,
+        blobs: &[&[u8]],
+        commitments: &[Commitment<Bls12_381>],
+        proofs: &[Proof<Bls12_381>],
+    ) -> Result<(), Error> {
+        for found in [commitments.len(), proofs.len()] {
+            if found != blobs.len() {
+                return Err(Error::MismatchedLengths {
+                    expected: blobs.len(),
+                    found,
+                });
+            }
+        }
+        for ((blob, commitment), proof) in blobs.iter().zip(commitments).zip(proofs) {
+            self.verify_blob_kzg_proof(blob, commitment, proof)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the point evaluation precompile on its 192 byte input
+    /// `versioned_hash | z | y | commitment | proof` and returns its output
+    /// `FIELD_ELEMENTS_PER_BLOB | BLS_MODULUS`, both as 32 byte big-endian words.
+    pub fn point_evaluation(&self, input: &[u8]) -> Result<[u8; 64], Error> {
+        if input.len() != POINT_EVALUATION_INPUT_LENGTH {
+            return Err(Error::MismatchedLengths {
+                expected: POINT_EVALUATION_INPUT_LENGTH,
+                found: input.len(),
+            });
+        }
+        let versioned_hash = &input[..32];
+        let z = bytes_to_bls_field(&input[32..64])?;
+        let y = bytes_to_bls_field(&input[64..96])?;
+        let commitment = bytes_to_kzg_commitment(&input[96..144])?;
+        let proof = bytes_to_kzg_proof(&input[144..192])?;
+
+        if kzg_to_versioned_hash(&commitment) != versioned_hash {
+            return Err(Error::VersionedHashMismatch);
+        }
+        self.verify_kzg_proof(&commitment, z, y, &proof)?;
+
+        let mut output = [0u8; 64];
+        output[24..32].copy_from_slice(&(FIELD_ELEMENTS_PER_BLOB as u64).to_be_bytes());
+        output[32..].copy_from_slice(&Fr::MODULUS.to_bytes_be());
+        Ok(output)
+    }
+
+    /// Evaluates the polynomial given by its evaluations over the bit-reversed
+    /// roots of unity at `z`.
+    pub fn evaluate_polynomial_in_evaluation_form(&self, polynomial: &[Fr], z: Fr) -> Fr {
+        evaluate_in_evaluation_form(&self.roots_of_unity_brp, polynomial, z)
+    }
+
+    fn compute_kzg_proof_impl(&self, polynomial: &[Fr], z: Fr) -> (Proof<Bls12_381>, Fr) {
+        let y = self.evaluate_polynomial_in_evaluation_form(polynomial, z);
+        let quotient = quotient_in_evaluation_form(&self.roots_of_unity_brp, polynomial, z, y);
+        let proof = Proof {
+            w: self.g1_lincomb(&quotient),
+            random_v: None,
+        };
+        (proof, y)
+    }
+
+    fn g1_lincomb(&self, scalars: &[Fr]) -> G1Affine {
+        G1Projective::msm_unchecked(&self.g1_lagrange_brp, scalars).into_affine()
+    }
+}
+
+/// Returns `0x01 | sha256(commitment)[1..]`.
+pub fn kzg_to_versioned_hash(commitment: &Commitment<Bls12_381>) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(BYTES_PER_COMMITMENT);
+    commitment
+        .0
+        .serialize_compressed(&mut bytes)
+        .expect("serialization into a vector does not fail");
+    let mut hash: [u8; 32] = Sha256::digest(&bytes).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// Parses a blob into its field elements, rejecting non-canonical encodings.
+pub fn blob_to_polynomial(blob: &[u8]) -> Result<Vec<Fr>, Error> {
+    if blob.len() != BYTES_PER_BLOB {
+        return Err(Error::MismatchedLengths {
+            expected: BYTES_PER_BLOB,
+            found: blob.len(),
+        });
+    }
+    blob.chunks(BYTES_PER_FIELD_ELEMENT)
+        .map(bytes_to_bls_field)
+        .collect()
+}
+
+/// Parses a 32 byte big-endian field element, which must be below the modulus.
+pub fn bytes_to_bls_field(bytes: &[u8]) -> Result<Fr, Error> {
+    if bytes.len() != BYTES_PER_FIELD_ELEMENT {
+        return Err(Error::MismatchedLengths {
+            expected: BYTES_PER_FIELD_ELEMENT,
+            found: bytes.len(),
+        });
+    }
+    let mut bits = Vec::with_capacity(8 * BYTES_PER_FIELD_ELEMENT);
+    for byte in bytes {
+        bits.extend((0..8).rev().map(|i| (byte >> i) & 1 == 1));
+    }
+    let bigint = <Fr as PrimeField>::BigInt::from_bits_be(&bits);
+    Fr::from_bigint(bigint).ok_or(Error::InvalidEncoding("field element is not canonical"))
+}
+
+pub fn bls_field_to_bytes(element: Fr) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&element.into_bigint().to_bytes_be());
+    bytes
+}
+
+/// Parses a compressed G1 point, which must lie in the prime order subgroup.
+pub fn bytes_to_kzg_commitment(bytes: &[u8]) -> Result<Commitment<Bls12_381>, Error> {
+    Ok(Commitment(bytes_to_g1(bytes, BYTES_PER_COMMITMENT)?))
+}
+
+pub fn bytes_to_kzg_proof(bytes: &[u8]) -> Result<Proof<Bls12_381>, Error> {
+    Ok(Proof {
+        w: bytes_to_g1(bytes, BYTES_PER_PROOF)?,
+        random_v: None,
+    })
+}
+
+fn bytes_to_g1(bytes: &[u8], expected: usize) -> Result<G1Affine, Error> {
+    if bytes.len() != expected {
+        return Err(Error::MismatchedLengths {
+            expected,
+            found: bytes.len(),
+        });
+    }
+    Ok(G1Affine::deserialize_compressed(bytes)?)
+}
+
+/// Derives the evaluation point of blob proofs from
+/// `sha256(FSBLOBVERIFY_V1_ | 4096 as u128 | blob | commitment)`.
+pub fn compute_challenge(blob: &[u8], commitment: &Commitment<Bls12_381>) -> Fr {
+    let mut hasher = Sha256::new();
+    hasher.update(FIAT_SHAMIR_PROTOCOL_DOMAIN);
+    hasher.update((FIELD_ELEMENTS_PER_BLOB as u128).to_be_bytes());
+    hasher.update(blob);
+    let mut bytes = Vec::with_capacity(BYTES_PER_COMMITMENT);
+    commitment
+        .0
+        .serialize_compressed(&mut bytes)
+        .expect("serialization into a vector does not fail");
+    hasher.update(&bytes);
+    Fr::from_be_bytes_mod_order(&hasher.finalize())
+}
+
+/// Reorders the elements so that the i-th element moves to the position given
+/// by reversing the bits of i.
+pub fn bit_reversal_permutation<T: Clone>(elements: &[T]) -> Vec<T> {
+    let n = elements.len();
+    assert!(n.is_power_of_two(), "length has to be a power of 2");
+    let bits = n.trailing_zeros();
+    (0..n)
+        .map(|i| match bits {
+            0 => elements[0].clone(),
+            _ => elements[i.reverse_bits() >> (usize::BITS - bits)].clone(),
+        })
+        .collect()
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() % 2 == 1 {
+        return Err(Error::InvalidEncoding("hex string has odd length"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| Error::InvalidEncoding("invalid hex digit"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::Field;
+    use ark_std::{test_rng, UniformRand};
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+
+    fn setup() -> &'static TrustedSetup {
+        static SETUP: OnceLock<TrustedSetup> = OnceLock::new();
+        SETUP.get_or_init(|| TrustedSetup::insecure_from_tau(Fr::from(1337u64)))
+    }
+
+    fn random_blob() -> Vec<u8> {
+        let rng = &mut test_rng();
+        (0..FIELD_ELEMENTS_PER_BLOB)
+            .flat_map(|_| bls_field_to_bytes(Fr::rand(rng)))
+            .collect()
+    }
+
+    #[test]
+    fn test_versioned_hash_of_empty_blob() {
+        let commitment = setup()
+            .blob_to_kzg_commitment(&[0u8; BYTES_PER_BLOB])
+            .unwrap();
+        assert!(commitment.0.is_zero());
+        let expected =
+            decode_hex("010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014");
+        assert_eq!(
+            kzg_to_versioned_hash(&commitment).to_vec(),
+            expected.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_field_element_encoding() {
+        let element = Fr::rand(&mut test_rng());
+        let bytes = bls_field_to_bytes(element);
+        assert_eq!(bytes_to_bls_field(&bytes).unwrap(), element);
+
+        let modulus = Fr::MODULUS.to_bytes_be();
+        assert!(matches!(
+            bytes_to_bls_field(&modulus),
+            Err(Error::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_bit_reversal_permutation() {
+        let elements: Vec<usize> = (0..8).collect();
+        assert_eq!(
+            bit_reversal_permutation(&elements),
+            vec![0, 4, 2, 6, 1, 5, 3, 7]
+        );
+        assert_eq!(
+            bit_reversal_permutation(&bit_reversal_permutation(&elements)),
+            elements
+        );
+    }
+
+    #[test]
+    fn test_load_trusted_setup() {
+        fn hex<T: CanonicalSerialize>(point: &T) -> String {
+            let mut bytes = vec![];
+            point.serialize_compressed(&mut bytes).unwrap();
+            bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+        }
+
+        let setup = setup();
+        let mut contents = format!("{}\n2\n", FIELD_ELEMENTS_PER_BLOB);
+        for point in bit_reversal_permutation(&setup.g1_lagrange_brp) {
+            contents += &format!("{}\n", hex(&point));
+        }
+        for point in &setup.vk.powers_of_h {
+            contents += &format!("{}\n", hex(point));
+        }
+        let path =
+            std::env::temp_dir().join(format!("ark-kzg-{}-trusted-setup.txt", std::process::id()));
+        fs::write(&path, &contents).unwrap();
+
+        let loaded = TrustedSetup::load(&path).unwrap();
+        assert_eq!(loaded.g1_lagrange_brp, setup.g1_lagrange_brp);
+        assert_eq!(loaded.vk, setup.vk);
+
+        let truncated: Vec<&str> = contents.lines().take(100).collect();
+        fs::write(&path, truncated.join("\n")).unwrap();
+        assert!(matches!(
+            TrustedSetup::load(&path),
+            Err(Error::InvalidSrs(_))
+        ));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_commitment_matches_monomial_srs() {
+        // committing to evaluations equals committing to the interpolated polynomial
+        use crate::kzg10::Kzg;
+        use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+
+        let tau = Fr::from(1337u64);
+        let pp = Kzg::<Bls12_381>::setup_from_tau(FIELD_ELEMENTS_PER_BLOB - 1, tau).unwrap();
+        let (powers, _) = Kzg::trim(&pp, FIELD_ELEMENTS_PER_BLOB - 1).unwrap();
+
+        let blob = random_blob();
+        let evaluations = bit_reversal_permutation(&blob_to_polynomial(&blob).unwrap());
+        let domain = Radix2EvaluationDomain::<Fr>::new(FIELD_ELEMENTS_PER_BLOB).unwrap();
+        let poly = DensePolynomial::from_coefficients_vec(domain.ifft(&evaluations));
+
+        let expected = Kzg::commit(&powers, &poly).unwrap();
+        assert_eq!(setup().blob_to_kzg_commitment(&blob).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_kzg_proof_outside_and_inside_domain() {
+        let setup = setup();
+        let blob = random_blob();
+        let polynomial = blob_to_polynomial(&blob).unwrap();
+        let commitment = setup.blob_to_kzg_commitment(&blob).unwrap();
+
+        let z = Fr::rand(&mut test_rng());
+        let (proof, y) = setup.compute_kzg_proof(&blob, z).unwrap();
+        setup.verify_kzg_proof(&commitment, z, y, &proof).unwrap();
+        assert!(setup
+            .verify_kzg_proof(&commitment, z, y + Fr::ONE, &proof)
+            .is_err());
+
+        let z = setup.roots_of_unity_brp[5];
+        let (proof, y) = setup.compute_kzg_proof(&blob, z).unwrap();
+        assert_eq!(y, polynomial[5]);
+        setup.verify_kzg_proof(&commitment, z, y, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_blob_kzg_proof() {
+        let setup = setup();
+        let blob = random_blob();
+        let commitment = setup.blob_to_kzg_commitment(&blob).unwrap();
+        let proof = setup.compute_blob_kzg_proof(&blob, &commitment).unwrap();
+        setup
+            .verify_blob_kzg_proof(&blob, &commitment, &proof)
+            .unwrap();
+
+        let mut other = blob.clone();
+        other[31] ^= 1;
+        assert!(setup
+            .verify_blob_kzg_proof(&other, &commitment, &proof)
+            .is_err());
+        setup
+            .verify_blob_kzg_proof_batch(&[&blob, &blob], &[commitment; 2], &[proof; 2])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_point_evaluation_precompile() {
+        let setup = setup();
+        let blob = random_blob();
+        let commitment = setup.blob_to_kzg_commitment(&blob).unwrap();
+        let z = Fr::rand(&mut test_rng());
+        let (proof, y) = setup.compute_kzg_proof(&blob, z).unwrap();
+
+        let mut input = kzg_to_versioned_hash(&commitment).to_vec();
+        input.extend(bls_field_to_bytes(z));
+        input.extend(bls_field_to_bytes(y));
+        commitment.0.serialize_compressed(&mut input).unwrap();
+        proof.w.serialize_compressed(&mut input).unwrap();
+
+        let output = setup.point_evaluation(&input).unwrap();
+        let expected = decode_hex(concat!(
+            "0000000000000000000000000000000000000000000000000000000000001000",
+            "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+        ));
+        assert_eq!(output.to_vec(), expected.unwrap());
+
+        let mut wrong_hash = input.clone();
+        wrong_hash[1] ^= 1;
+        assert!(matches!(
+            setup.point_evaluation(&wrong_hash),
+            Err(Error::VersionedHashMismatch)
+        ));
+        let mut wrong_value = input.clone();
+        wrong_value[95] ^= 1;
+        assert!(matches!(
+            setup.point_evaluation(&wrong_value),
+            Err(Error::InvalidProof)
+        ));
+    }
+
+    /// Value of a field in the `data.yaml` files of the consensus spec tests.
+    #[derive(Debug, PartialEq)]
+    enum Value {
+        Bytes(Vec<u8>),
+        Bool(bool),
+        Null,
+        List(Vec<Value>),
+    }
+
+    struct TestVector {
+        input: Vec<(String, Value)>,
+        output: Value,
+    }
+
+    impl TestVector {
+        fn input(&self, key: &str) -> &Value {
+            &self
+                .input
+                .iter()
+                .find(|(k, _)| k == key)
+                .expect("missing input")
+                .1
+        }
+    }
+
+    fn parse_scalar(value: &str) -> Value {
+        match value.trim().trim_matches('\'').trim_matches('"') {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "null" | "~" => Value::Null,
+            hex => Value::Bytes(decode_hex(hex).expect("invalid hex in test vector")),
+        }
+    }
+
+    /// Parses the subset of YAML used by the test vectors: an `input` mapping
+    /// of scalars or lists and an `output` scalar or list.
+    fn parse_test_vector(contents: &str) -> TestVector {
+        let mut input: Vec<(String, Value)> = vec![];
+        let mut output = Value::Null;
+        let mut in_output = false;
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let trimmed = line.trim();
+            if let Some(rest) = trimmed.strip_prefix("- ") {
+                let list = match in_output {
+                    true => &mut output,
+                    false => &mut input.last_mut().expect("list without key").1,
+                };
+                if let Value::List(items) = list {
+                    items.push(parse_scalar(rest));
+                }
+            } else if let Some(rest) = line.strip_prefix("output:") {
+                in_output = true;
+                output = match rest.trim() {
+                    "" => Value::List(vec![]),
+                    "[]" => Value::List(vec![]),
+                    scalar => parse_scalar(scalar),
+                };
+            } else if line.starts_with("input:") {
+                in_output = false;
+            } else {
+                let (key, rest) = trimmed.split_once(':').expect("expected a key");
+                let value = match rest.trim() {
+                    "" => Value::List(vec![]),
+                    "[]" => Value::List(vec![]),
+                    scalar => parse_scalar(scalar),
+                };
+                input.push((key.to_string(), value));
+            }
+        }
+        TestVector { input, output }
+    }
+
+    fn bytes(value: &Value) -> &[u8] {
+        match value {
+            Value::Bytes(bytes) => bytes,
+            _ => panic!("expected bytes"),
+        }
+    }
+
+    fn list(value: &Value) -> Vec<&[u8]> {
+        match value {
+            Value::List(items) => items.iter().map(bytes).collect(),
+            _ => panic!("expected a list"),
+        }
+    }
+
+    fn to_bytes(element: &impl CanonicalSerialize) -> Value {
+        let mut bytes = vec![];
+        element.serialize_compressed(&mut bytes).unwrap();
+        Value::Bytes(bytes)
+    }
+
+    /// Runs one test vector and returns the output in the test vector format,
+    /// `null` standing for inputs the spec rejects.
+    fn run_test_vector(setup: &TrustedSetup, handler: &str, vector: &TestVector) -> Value {
+        let verified = |result: Result<(), Error>| match result {
+            Ok(()) => Ok(Value::Bool(true)),
+            Err(Error::InvalidProof) => Ok(Value::Bool(false)),
+            Err(err) => Err(err),
+        };
+        let result = (|| match handler {
+            "blob_to_kzg_commitment" => {
+                let blob = bytes(vector.input("blob"));
+                Ok(to_bytes(&setup.blob_to_kzg_commitment(blob)?.0))
+            }
+            "compute_kzg_proof" => {
+                let blob = bytes(vector.input("blob"));
+                let z = bytes_to_bls_field(bytes(vector.input("z")))?;
+                let (proof, y) = setup.compute_kzg_proof(blob, z)?;
+                Ok(Value::List(vec![
+                    to_bytes(&proof.w),
+                    Value::Bytes(bls_field_to_bytes(y).to_vec()),
+                ]))
+            }
+            "compute_blob_kzg_proof" => {
+                let blob = bytes(vector.input("blob"));
+                let commitment = bytes_to_kzg_commitment(bytes(vector.input("commitment")))?;
+                Ok(to_bytes(
+                    &setup.compute_blob_kzg_proof(blob, &commitment)?.w,
+                ))
+            }
+            "verify_kzg_proof" => {
+                let commitment = bytes_to_kzg_commitment(bytes(vector.input("commitment")))?;
+                let z = bytes_to_bls_field(bytes(vector.input("z")))?;
+                let y = bytes_to_bls_field(bytes(vector.input("y")))?;
+                let proof = bytes_to_kzg_proof(bytes(vector.input("proof")))?;
+                verified(setup.verify_kzg_proof(&commitment, z, y, &proof))
+            }
+            "verify_blob_kzg_proof" => {
+                let blob = bytes(vector.input("blob"));
+                let commitment = bytes_to_kzg_commitment(bytes(vector.input("commitment")))?;
+                let proof = bytes_to_kzg_proof(bytes(vector.input("proof")))?;
+                verified(setup.verify_blob_kzg_proof(blob, &commitment, &proof))
+            }
+            "verify_blob_kzg_proof_batch" => {
+                let blobs = list(vector.input("blobs"));
+                let commitments = list(vector.input("commitments"))
+                    .into_iter()
+                    .map(bytes_to_kzg_commitment)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let proofs = list(vector.input("proofs"))
+                    .into_iter()
+                    .map(bytes_to_kzg_proof)
+                    .collect::<Result<Vec<_>, _>>()?;
+                verified(setup.verify_blob_kzg_proof_batch(&blobs, &commitments, &proofs))
+            }
+            _ => panic!("unknown handler {}", handler),
+        })();
+        result.unwrap_or(Value::Null)
+    }
+
+    /// Runs every `<handler>/.../data.yaml` below `root` and returns the number
+    /// of test vectors.
+    fn run_test_vectors(setup: &TrustedSetup, root: &Path) -> usize {
+        fn data_files(dir: &Path, files: &mut Vec<PathBuf>) {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    data_files(&path, files);
+                } else if path.file_name().is_some_and(|name| name == "data.yaml") {
+                    files.push(path);
+                }
+            }
+        }
+
+        let mut files = vec![];
+        data_files(root, &mut files);
+        for file in &files {
+            let handler = file.strip_prefix(root).unwrap().iter().next().unwrap();
+            let handler = handler.to_str().unwrap();
+            let vector = parse_test_vector(&fs::read_to_string(file).unwrap());
+            let output = run_test_vector(setup, handler, &vector);
+            assert_eq!(output, vector.output, "{}", file.display());
+        }
+        files.len()
+    }
+
+    #[test]
+    fn test_setup_independent_vectors() {
+        // cases from the consensus spec format whose outputs do not depend on τ
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("vectors/eip4844");
+        assert!(run_test_vectors(setup(), &root) > 0);
+    }
+
+    #[test]
+    #[ignore = "needs the published test vectors and trusted setup, see EIP4844_TEST_VECTORS"]
+    fn test_published_vectors() {
+        // EIP4844_TRUSTED_SETUP points to c-kzg-4844's trusted_setup.txt and
+        // EIP4844_TEST_VECTORS to the tests/general/deneb/kzg directory of the
+        // consensus spec tests
+        let setup = TrustedSetup::load(std::env::var("EIP4844_TRUSTED_SETUP").unwrap()).unwrap();
+        let root = PathBuf::from(std::env::var("EIP4844_TEST_VECTORS").unwrap());
+        assert!(run_test_vectors(&setup, &root) > 0);
+    }
+}
@@ -0,0 +1,118 @@
+use std::{fmt, io};
+
+use ark_serialize::SerializationError;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Parameters must support at least degree one polynomials
+    DegreeIsZero,
+    /// The requested degree exceeds the maximum degree of the parameters
+    UnsupportedDegree {
+        requested: usize,
+        supported: usize,
+    },
+    /// The polynomial has more coefficients than there are powers in the prover key
+    TooManyCoefficients {
+        num_coefficients: usize,
+        num_powers: usize,
+    },
+    /// Batched openings got a different number of elements than expected
+    MismatchedLengths {
+        expected: usize,
+        found: usize,
+    },
+    /// Interpolation needs pairwise distinct points
+    DuplicatePoints,
+    /// The verifier key has too few G2 powers to open at that many points
+    TooManyPoints {
+        num_points: usize,
+        max_points: usize,
+    },
+    /// Multi-open queries are empty, repeated or reference an unknown polynomial
+    InvalidQuery,
+    /// The parameters have no powers of `Γ` to blind commitments with
+    HidingUnsupported,
+    /// A constant blinding polynomial is revealed by the first opening
+    HidingBoundIsZero,
+    /// The pairing check of an opening proof failed
+    InvalidProof,
+    /// Loaded parameters are malformed or not powers of a single `τ`
+    InvalidSrs(&'static str),
+    /// A powers-of-tau ceremony contribution does not verify
+    InvalidContribution {
+        index: usize,
+        reason: &'static str,
+    },
+    /// Bytes are not a canonical field element or do not have the expected layout
+    InvalidEncoding(&'static str),
+    /// The versioned hash does not belong to the KZG commitment
+    VersionedHashMismatch,
+    Io(io::Error),
+    Serialization(SerializationError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DegreeIsZero => write!(f, "cannot generate parameters for degree zero"),
+            Error::UnsupportedDegree {
+                requested,
+                supported,
+            } => write!(
+                f,
+                "requested degree {} but the parameters only support degree {}",
+                requested, supported
+            ),
+            Error::TooManyCoefficients {
+                num_coefficients,
+                num_powers,
+            } => write!(
+                f,
+                "polynomial has {} coefficients but only {} powers are available",
+                num_coefficients, num_powers
+            ),
+            Error::MismatchedLengths { expected, found } => {
+                write!(f, "expected {} elements but found {}", expected, found)
+            }
+            Error::DuplicatePoints => write!(f, "evaluation points are not distinct"),
+            Error::TooManyPoints {
+                num_points,
+                max_points,
+            } => write!(
+                f,
+                "cannot open at {} points, the verifier key supports at most {}",
+                num_points, max_points
+            ),
+            Error::InvalidQuery => write!(f, "invalid multi-open queries"),
+            Error::HidingUnsupported => write!(f, "parameters do not support hiding"),
+            Error::HidingBoundIsZero => {
+                write!(f, "hiding commitments need a hiding bound of at least one")
+            }
+            Error::InvalidProof => write!(f, "opening proof does not verify"),
+            Error::InvalidSrs(reason) => write!(f, "invalid parameters: {}", reason),
+            Error::InvalidContribution { index, reason } => {
+                write!(f, "invalid contribution {}: {}", index, reason)
+            }
+            Error::InvalidEncoding(reason) => write!(f, "invalid encoding: {}", reason),
+            Error::VersionedHashMismatch => {
+                write!(f, "versioned hash does not match the commitment")
+            }
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Serialization(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<SerializationError> for Error {
+    fn from(err: SerializationError) -> Self {
+        Error::Serialization(err)
+    }
+}
@@ -0,0 +1,597 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::scalar_mul::ScalarMul;
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup, VariableBaseMSM};
+use ark_ff::{Field, One, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, Polynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::marker::PhantomData;
+use ark_std::UniformRand;
+use rand_core::RngCore;
+
+use crate::Error;
+
+pub type UniPoly<E> = DensePolynomial<<E as Pairing>::ScalarField>;
+
+/// Structured reference string `([τ^i]G1, [τ^i]Γ, [τ^i]G2)` up to the maximum degree
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UniversalParams<E: Pairing> {
+    pub powers_of_g: Vec<E::G1Affine>,
+    /// Powers of a second G1 generator `Γ` with unknown discrete logarithm,
+    /// empty when the parameters do not support hiding commitments
+    pub powers_of_gamma_g: Vec<E::G1Affine>,
+    pub powers_of_h: Vec<E::G2Affine>,
+}
+
+impl<E: Pairing> UniversalParams<E> {
+    /// Zero for parameters without any G1 power, which support no degree
+    pub fn max_degree(&self) -> usize {
+        self.powers_of_g.len().saturating_sub(1)
+    }
+}
+
+/// Prover key: the G1 powers needed to commit to polynomials up to the trimmed degree
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Powers<E: Pairing> {
+    pub powers_of_g: Vec<E::G1Affine>,
+    pub powers_of_gamma_g: Vec<E::G1Affine>,
+}
+
+impl<E: Pairing> Powers<E> {
+    pub fn size(&self) -> usize {
+        self.powers_of_g.len()
+    }
+}
+
+/// Verifier key: the G1 generator and the G2 powers up to the trimmed degree,
+/// which multi-point openings need to commit to vanishing polynomials
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierKey<E: Pairing> {
+    pub g: E::G1Affine,
+    pub gamma_g: Option<E::G1Affine>,
+    pub powers_of_h: Vec<E::G2Affine>,
+}
+
+impl<E: Pairing> VerifierKey<E> {
+    pub fn h(&self) -> Result<E::G2Affine, Error> {
+        self.powers_of_h
+            .first()
+            .copied()
+            .ok_or(Error::InvalidSrs("the verifier key has no G2 powers"))
+    }
+
+    pub fn beta_h(&self) -> Result<E::G2Affine, Error> {
+        self.powers_of_h
+            .get(1)
+            .copied()
+            .ok_or(Error::InvalidSrs("at least two G2 powers are required"))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Commitment<E: Pairing>(pub E::G1Affine);
+
+/// Opening proof: commitment to the quotient `(f(X) - f(z)) / (X - z)`, for
+/// hiding commitments together with the evaluation `r(z)` of the blinding polynomial
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof<E: Pairing> {
+    pub w: E::G1Affine,
+    pub random_v: Option<E::ScalarField>,
+}
+
+/// Blinding polynomial `r(X)` of a hiding commitment `[f(τ)]G1 + [r(τ)]Γ`,
+/// which the committer keeps secret and needs again for every opening
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Randomness<E: Pairing> {
+    pub blinding_polynomial: UniPoly<E>,
+}
+
+/// KZG10 commitment scheme for univariate polynomials over the pairing `E`
+pub struct Kzg<E: Pairing> {
+    _pairing: PhantomData<E>,
+}
+
+impl<E: Pairing> Kzg<E> {
+    /// Generates universal parameters supporting (hiding) commitments to
+    /// polynomials up to `max_degree` from a freshly sampled toxic waste
+    pub fn setup<R: RngCore>(max_degree: usize, rng: &mut R) -> Result<UniversalParams<E>, Error> {
+        let tau = E::ScalarField::rand(rng);
+        let gamma = E::ScalarField::rand(rng);
+        Self::setup_from_toxic_waste(max_degree, tau, Some(gamma))
+    }
+
+    /// Generates universal parameters without hiding support from the toxic
+    /// waste `tau`. Anybody knowing `tau` can forge openings, so it must be
+    /// discarded afterwards.
+    pub fn setup_from_tau(
+        max_degree: usize,
+        tau: E::ScalarField,
+    ) -> Result<UniversalParams<E>, Error> {
+        Self::setup_from_toxic_waste(max_degree, tau, None)
+    }
+
+    fn setup_from_toxic_waste(
+        max_degree: usize,
+        tau: E::ScalarField,
+        gamma: Option<E::ScalarField>,
+    ) -> Result<UniversalParams<E>, Error> {
+        if max_degree < 1 {
+            return Err(Error::DegreeIsZero);
+        }
+
+        let powers_of_tau: Vec<E::ScalarField> =
+            ark_std::iter::successors(Some(E::ScalarField::one()), |power| Some(*power * tau))
+                .take(max_degree + 1)
+                .collect();
+        let powers_of_g = E::G1::generator().batch_mul(&powers_of_tau);
+        let powers_of_gamma_g = match gamma {
+            Some(gamma) => (E::G1::generator() * gamma).batch_mul(&powers_of_tau),
+            None => vec![],
+        };
+        let powers_of_h = E::G2::generator().batch_mul(&powers_of_tau);
+
+        Ok(UniversalParams {
+            powers_of_g,
+            powers_of_gamma_g,
+            powers_of_h,
+        })
+    }
+
+    /// Specializes the universal parameters to polynomials up to `supported_degree`
+    pub fn trim(
+        pp: &UniversalParams<E>,
+        supported_degree: usize,
+    ) -> Result<(Powers<E>, VerifierKey<E>), Error> {
+        if supported_degree < 1 {
+            return Err(Error::DegreeIsZero);
+        }
+        if supported_degree > pp.max_degree() {
+            return Err(Error::UnsupportedDegree {
+                requested: supported_degree,
+                supported: pp.max_degree(),
+            });
+        }
+        if pp.powers_of_h.len() < 2 {
+            return Err(Error::InvalidSrs("at least two G2 powers are required"));
+        }
+
+        let num_powers_of_gamma_g = pp.powers_of_gamma_g.len().min(supported_degree + 1);
+        let powers = Powers {
+            powers_of_g: pp.powers_of_g[..=supported_degree].to_vec(),
+            powers_of_gamma_g: pp.powers_of_gamma_g[..num_powers_of_gamma_g].to_vec(),
+        };
+        // imported transcripts may carry fewer G2 than G1 powers
+        let num_powers_of_h = pp.powers_of_h.len().min(supported_degree + 1);
+        let vk = VerifierKey {
+            g: pp.powers_of_g[0],
+            gamma_g: pp.powers_of_gamma_g.first().copied(),
+            powers_of_h: pp.powers_of_h[..num_powers_of_h].to_vec(),
+        };
+        Ok((powers, vk))
+    }
+
+    /// Commits to `polynomial` as `[f(τ)]G1 = Σ f_i·[τ^i]G1`
+    pub fn commit(powers: &Powers<E>, polynomial: &UniPoly<E>) -> Result<Commitment<E>, Error> {
+        Self::check_degree(polynomial, powers)?;
+        let commitment = E::G1::msm_unchecked(&powers.powers_of_g, &polynomial.coeffs);
+        Ok(Commitment(commitment.into_affine()))
+    }
+
+    /// Computes a proof that `polynomial` evaluates to `polynomial(point)`
+    pub fn open(
+        powers: &Powers<E>,
+        polynomial: &UniPoly<E>,
+        point: E::ScalarField,
+    ) -> Result<Proof<E>, Error> {
+        Self::check_degree(polynomial, powers)?;
+        let witness = divide_by_linear(polynomial, point);
+        let w = E::G1::msm_unchecked(&powers.powers_of_g, &witness.coeffs);
+        Ok(Proof {
+            w: w.into_affine(),
+            random_v: None,
+        })
+    }
+
+    /// Commits to `polynomial` as `[f(τ)]G1 + [r(τ)]Γ` with a fresh blinding
+    /// polynomial `r` of degree `hiding_bound`, so `r(τ)` stays uniform after
+    /// revealing `r` at up to `hiding_bound` opening points. The bound must be
+    /// at least one.
+    pub fn commit_hiding<R: RngCore>(
+        powers: &Powers<E>,
+        polynomial: &UniPoly<E>,
+        hiding_bound: usize,
+        rng: &mut R,
+    ) -> Result<(Commitment<E>, Randomness<E>), Error> {
+        Self::check_degree(polynomial, powers)?;
+        Self::check_hiding_bound(hiding_bound, powers)?;
+
+        let blinding_polynomial = UniPoly::<E>::rand(hiding_bound, rng);
+        let commitment = E::G1::msm_unchecked(&powers.powers_of_g, &polynomial.coeffs)
+            + E::G1::msm_unchecked(&powers.powers_of_gamma_g, &blinding_polynomial.coeffs);
+        Ok((
+            Commitment(commitment.into_affine()),
+            Randomness {
+                blinding_polynomial,
+            },
+        ))
+    }
+
+    /// Opens a hiding commitment: the witness also commits to the quotient of
+    /// the blinding polynomial and the proof reveals `r(point)`
+    pub fn open_hiding(
+        powers: &Powers<E>,
+        polynomial: &UniPoly<E>,
+        point: E::ScalarField,
+        randomness: &Randomness<E>,
+    ) -> Result<Proof<E>, Error> {
+        Self::check_degree(polynomial, powers)?;
+        let blinding_polynomial = &randomness.blinding_polynomial;
+        Self::check_hiding_bound(blinding_polynomial.degree(), powers)?;
+
+        let witness = divide_by_linear(polynomial, point);
+        let blinding_witness = divide_by_linear(blinding_polynomial, point);
+        let w = E::G1::msm_unchecked(&powers.powers_of_g, &witness.coeffs)
+            + E::G1::msm_unchecked(&powers.powers_of_gamma_g, &blinding_witness.coeffs);
+        Ok(Proof {
+            w: w.into_affine(),
+            random_v: Some(blinding_polynomial.evaluate(&point)),
+        })
+    }
+
+    /// Checks that `commitment` opens to `value` at `point`, i.e.
+    /// `e(C - [v]G1 - [r(z)]Γ, H) = e(W, [τ]H - [z]H)`
+    pub fn verify(
+        vk: &VerifierKey<E>,
+        commitment: &Commitment<E>,
+        point: E::ScalarField,
+        value: E::ScalarField,
+        proof: &Proof<E>,
+    ) -> Result<(), Error> {
+        let mut lhs = commitment.0.into_group() - vk.g * value;
+        if let Some(random_v) = proof.random_v {
+            let gamma_g = vk.gamma_g.ok_or(Error::HidingUnsupported)?;
+            lhs -= gamma_g * random_v;
+        }
+        let (h, beta_h) = (vk.h()?, vk.beta_h()?);
+        let rhs = beta_h.into_group() - h * point;
+        let check = E::multi_pairing(
+            [lhs.into_affine(), (-proof.w.into_group()).into_affine()],
+            [h, rhs.into_affine()],
+        );
+
+        match check.is_zero() {
+            true => Ok(()),
+            false => Err(Error::InvalidProof),
+        }
+    }
+
+    pub(crate) fn check_degree(polynomial: &UniPoly<E>, powers: &Powers<E>) -> Result<(), Error> {
+        let num_coefficients = polynomial.coeffs.len();
+        if num_coefficients > powers.size() {
+            return Err(Error::TooManyCoefficients {
+                num_coefficients,
+                num_powers: powers.size(),
+            });
+        }
+        Ok(())
+    }
+
+    fn check_hiding_bound(hiding_bound: usize, powers: &Powers<E>) -> Result<(), Error> {
+        let num_powers = powers.powers_of_gamma_g.len();
+        if num_powers == 0 {
+            return Err(Error::HidingUnsupported);
+        }
+        if hiding_bound == 0 {
+            return Err(Error::HidingBoundIsZero);
+        }
+        if hiding_bound >= num_powers {
+            return Err(Error::UnsupportedDegree {
+                requested: hiding_bound,
+                supported: num_powers - 1,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Computes `q(X) = (f(X) - f(z)) / (X - z)` by synthetic division
+pub fn divide_by_linear<F: Field>(polynomial: &DensePolynomial<F>, point: F) -> DensePolynomial<F> {
+    let coeffs = &polynomial.coeffs;
+    if coeffs.len() < 2 {
+        return DensePolynomial::zero();
+    }
+
+    let mut quotient = vec![F::zero(); coeffs.len() - 1];
+    let mut acc = F::zero();
+    for i in (1..coeffs.len()).rev() {
+        acc = coeffs[i] + point * acc;
+        quotient[i - 1] = acc;
+    }
+    DensePolynomial::from_coefficients_vec(quotient)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    type Kzg381 = Kzg<Bls12_381>;
+
+    #[test]
+    fn test_commit_open_verify() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(15, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 15).unwrap();
+
+        let f = UniPoly::<Bls12_381>::rand(15, rng);
+        let comm = Kzg381::commit(&powers, &f).unwrap();
+        let point = Fr::rand(rng);
+        let proof = Kzg381::open(&powers, &f, point).unwrap();
+
+        assert!(Kzg381::verify(&vk, &comm, point, f.evaluate(&point), &proof).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_value_is_rejected() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(7, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 7).unwrap();
+
+        let f = UniPoly::<Bls12_381>::rand(7, rng);
+        let comm = Kzg381::commit(&powers, &f).unwrap();
+        let point = Fr::rand(rng);
+        let proof = Kzg381::open(&powers, &f, point).unwrap();
+        let value = f.evaluate(&point) + Fr::one();
+
+        assert!(matches!(
+            Kzg381::verify(&vk, &comm, point, value, &proof),
+            Err(Error::InvalidProof)
+        ));
+    }
+
+    #[test]
+    fn test_verify_without_beta_h() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(3, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 3).unwrap();
+
+        let f = UniPoly::<Bls12_381>::rand(3, rng);
+        let comm = Kzg381::commit(&powers, &f).unwrap();
+        let point = Fr::rand(rng);
+        let proof = Kzg381::open(&powers, &f, point).unwrap();
+
+        let vk = VerifierKey {
+            powers_of_h: vk.powers_of_h[..1].to_vec(),
+            ..vk
+        };
+        assert!(matches!(
+            Kzg381::verify(&vk, &comm, point, f.evaluate(&point), &proof),
+            Err(Error::InvalidSrs(_))
+        ));
+    }
+
+    #[test]
+    fn test_trim_above_max_degree() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(4, rng).unwrap();
+        assert!(matches!(
+            Kzg381::trim(&pp, 5),
+            Err(Error::UnsupportedDegree {
+                requested: 5,
+                supported: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn test_trim_malformed_params() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(4, rng).unwrap();
+        let empty = UniversalParams::<Bls12_381> {
+            powers_of_g: vec![],
+            powers_of_gamma_g: vec![],
+            powers_of_h: vec![],
+        };
+        assert_eq!(empty.max_degree(), 0);
+        assert!(matches!(
+            Kzg381::trim(&empty, 1),
+            Err(Error::UnsupportedDegree { .. })
+        ));
+
+        let without_g2 = UniversalParams {
+            powers_of_h: pp.powers_of_h[..1].to_vec(),
+            ..pp
+        };
+        assert!(matches!(
+            Kzg381::trim(&without_g2, 2),
+            Err(Error::InvalidSrs(_))
+        ));
+    }
+
+    #[test]
+    fn test_commit_above_trimmed_degree() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(8, rng).unwrap();
+        let (powers, _) = Kzg381::trim(&pp, 4).unwrap();
+        let f = UniPoly::<Bls12_381>::rand(5, rng);
+        assert!(Kzg381::commit(&powers, &f).is_err());
+    }
+
+    #[test]
+    fn test_divide_by_linear() {
+        // (X^2 + 3X + 5) = (X + 4)(X - 1) + 9
+        let f = DensePolynomial::from_coefficients_vec(vec![
+            Fr::from(5u64),
+            Fr::from(3u64),
+            Fr::from(1u64),
+        ]);
+        let q = divide_by_linear(&f, Fr::one());
+        assert_eq!(q.coeffs, vec![Fr::from(4u64), Fr::from(1u64)]);
+    }
+
+    #[test]
+    fn test_hiding_commitments() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(10, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 10).unwrap();
+
+        let f = UniPoly::<Bls12_381>::rand(10, rng);
+        let (comm_a, rand_a) = Kzg381::commit_hiding(&powers, &f, 1, rng).unwrap();
+        let (comm_b, rand_b) = Kzg381::commit_hiding(&powers, &f, 1, rng).unwrap();
+        assert_ne!(comm_a, comm_b);
+        assert_ne!(comm_a, Kzg381::commit(&powers, &f).unwrap());
+
+        let point = Fr::rand(rng);
+        let value = f.evaluate(&point);
+        let proof_a = Kzg381::open_hiding(&powers, &f, point, &rand_a).unwrap();
+        let proof_b = Kzg381::open_hiding(&powers, &f, point, &rand_b).unwrap();
+        assert!(Kzg381::verify(&vk, &comm_a, point, value, &proof_a).is_ok());
+        assert!(Kzg381::verify(&vk, &comm_b, point, value, &proof_b).is_ok());
+
+        // the randomness is bound to its commitment
+        assert!(Kzg381::verify(&vk, &comm_b, point, value, &proof_a).is_err());
+        let unblinded = Proof {
+            random_v: None,
+            ..proof_a
+        };
+        assert!(Kzg381::verify(&vk, &comm_a, point, value, &unblinded).is_err());
+    }
+
+    #[test]
+    fn test_hiding_bound() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(8, rng).unwrap();
+        let (powers, _) = Kzg381::trim(&pp, 4).unwrap();
+        let f = UniPoly::<Bls12_381>::rand(4, rng);
+
+        let (_, randomness) = Kzg381::commit_hiding(&powers, &f, 4, rng).unwrap();
+        assert_eq!(randomness.blinding_polynomial.degree(), 4);
+        assert!(matches!(
+            Kzg381::commit_hiding(&powers, &f, 5, rng),
+            Err(Error::UnsupportedDegree {
+                requested: 5,
+                supported: 4
+            })
+        ));
+        assert!(matches!(
+            Kzg381::commit_hiding(&powers, &f, 0, rng),
+            Err(Error::HidingBoundIsZero)
+        ));
+
+        // nor can a constant blinding polynomial be opened
+        let randomness = Randomness {
+            blinding_polynomial: UniPoly::<Bls12_381>::from_coefficients_vec(vec![Fr::one()]),
+        };
+        assert!(matches!(
+            Kzg381::open_hiding(&powers, &f, Fr::rand(rng), &randomness),
+            Err(Error::HidingBoundIsZero)
+        ));
+    }
+
+    #[test]
+    fn test_hiding_unsupported_without_gamma() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup_from_tau(4, Fr::rand(rng)).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 4).unwrap();
+        assert!(vk.gamma_g.is_none());
+
+        let f = UniPoly::<Bls12_381>::rand(4, rng);
+        assert!(matches!(
+            Kzg381::commit_hiding(&powers, &f, 1, rng),
+            Err(Error::HidingUnsupported)
+        ));
+    }
+
+    /// Differential tests against `ark_poly_commit` sharing the same SRS
+    mod ark_poly_commit_compat {
+        use super::*;
+        use ark_poly_commit::kzg10::{self, KZG10};
+        use std::borrow::Cow;
+
+        type ArkKzg = KZG10<Bls12_381, UniPoly<Bls12_381>>;
+
+        fn ark_keys(
+            powers: &Powers<Bls12_381>,
+            vk: &VerifierKey<Bls12_381>,
+        ) -> (
+            kzg10::Powers<'static, Bls12_381>,
+            kzg10::VerifierKey<Bls12_381>,
+        ) {
+            // hiding is not used, so the gamma powers are never read
+            let ark_powers = kzg10::Powers {
+                powers_of_g: Cow::Owned(powers.powers_of_g.clone()),
+                powers_of_gamma_g: Cow::Owned(powers.powers_of_g.clone()),
+            };
+            let ark_vk = kzg10::VerifierKey {
+                g: vk.g,
+                gamma_g: vk.g,
+                h: vk.h().unwrap(),
+                beta_h: vk.beta_h().unwrap(),
+                prepared_h: vk.h().unwrap().into(),
+                prepared_beta_h: vk.beta_h().unwrap().into(),
+            };
+            (ark_powers, ark_vk)
+        }
+
+        fn to_bytes(value: impl CanonicalSerialize) -> Vec<u8> {
+            let mut bytes = vec![];
+            value.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        }
+
+        #[test]
+        fn test_same_commitments_and_proofs() {
+            let rng = &mut test_rng();
+            let pp = Kzg381::setup_from_tau(31, Fr::rand(rng)).unwrap();
+            for degree in [1, 2, 7, 16, 31] {
+                let (powers, vk) = Kzg381::trim(&pp, degree).unwrap();
+                let (ark_powers, _) = ark_keys(&powers, &vk);
+
+                let f = UniPoly::<Bls12_381>::rand(degree, rng);
+                let point = Fr::rand(rng);
+
+                let comm = Kzg381::commit(&powers, &f).unwrap();
+                let (ark_comm, ark_rand) = ArkKzg::commit(&ark_powers, &f, None, None).unwrap();
+                assert_eq!(to_bytes(comm.0), to_bytes(ark_comm.0));
+
+                let proof = Kzg381::open(&powers, &f, point).unwrap();
+                let ark_proof = ArkKzg::open(&ark_powers, &f, point, &ark_rand).unwrap();
+                assert_eq!(to_bytes(proof.w), to_bytes(ark_proof.w));
+            }
+        }
+
+        #[test]
+        fn test_cross_verification() {
+            let rng = &mut test_rng();
+            let pp = Kzg381::setup(10, rng).unwrap();
+            let (powers, vk) = Kzg381::trim(&pp, 10).unwrap();
+            let (ark_powers, ark_vk) = ark_keys(&powers, &vk);
+
+            let f = UniPoly::<Bls12_381>::rand(10, rng);
+            let point = Fr::rand(rng);
+            let value = f.evaluate(&point);
+
+            let comm = Kzg381::commit(&powers, &f).unwrap();
+            let proof = Kzg381::open(&powers, &f, point).unwrap();
+            let ark_comm = kzg10::Commitment(comm.0);
+            let ark_proof = kzg10::Proof {
+                w: proof.w,
+                random_v: None,
+            };
+            assert!(ArkKzg::check(&ark_vk, &ark_comm, point, value, &ark_proof).unwrap());
+            assert!(
+                !ArkKzg::check(&ark_vk, &ark_comm, point, value + Fr::one(), &ark_proof).unwrap()
+            );
+
+            let (ark_comm, ark_rand) = ArkKzg::commit(&ark_powers, &f, None, None).unwrap();
+            let ark_proof = ArkKzg::open(&ark_powers, &f, point, &ark_rand).unwrap();
+            let comm = Commitment(ark_comm.0);
+            let proof = Proof {
+                w: ark_proof.w,
+                random_v: None,
+            };
+            assert!(Kzg381::verify(&vk, &comm, point, value, &proof).is_ok());
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Commitments to polynomials given by their evaluations over a radix-2 domain,
+//! using the Lagrange-basis SRS `[L_i(τ)]G1` instead of the powers of `τ`.
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{batch_inversion, Field};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+
+use crate::kzg10::{Commitment, Kzg, Powers, Proof};
+use crate::Error;
+
+/// Prover key in the Lagrange basis of `domain`: `[L_i(τ)]G1` where `L_i` is
+/// one on `ω^i` and zero on the other elements of the domain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LagrangePowers<E: Pairing> {
+    pub powers_of_g: Vec<E::G1Affine>,
+    pub domain: Radix2EvaluationDomain<E::ScalarField>,
+}
+
+impl<E: Pairing> Kzg<E> {
+    /// Converts the monomial powers `[τ^j]G1` into the Lagrange basis of the
+    /// radix-2 domain of at least `domain_size` elements. Since
+    /// `L_i(τ) = 1/n Σ_j ω^(-ij) τ^j`, this is an inverse FFT in the group.
+    pub fn lagrange_powers(
+        powers: &Powers<E>,
+        domain_size: usize,
+    ) -> Result<LagrangePowers<E>, Error> {
+        let domain = Radix2EvaluationDomain::new(domain_size).ok_or(Error::UnsupportedDegree {
+            requested: domain_size - 1,
+            supported: powers.size() - 1,
+        })?;
+        if domain.size() > powers.size() {
+            return Err(Error::UnsupportedDegree {
+                requested: domain.size() - 1,
+                supported: powers.size() - 1,
+            });
+        }
+
+        let monomial: Vec<E::G1> = powers.powers_of_g[..domain.size()]
+            .iter()
+            .map(|power| (*power).into())
+            .collect();
+        Ok(LagrangePowers {
+            powers_of_g: E::G1::normalize_batch(&domain.ifft(&monomial)),
+            domain,
+        })
+    }
+
+    /// Commits to the polynomial taking `evaluations[i]` at `ω^i`; equal to
+    /// the commitment to its interpolation in coefficient form
+    pub fn commit_lagrange(
+        powers: &LagrangePowers<E>,
+        evaluations: &[E::ScalarField],
+    ) -> Result<Commitment<E>, Error> {
+        check_evaluations(powers, evaluations)?;
+        let commitment = E::G1::msm_unchecked(&powers.powers_of_g, evaluations);
+        Ok(Commitment(commitment.into_affine()))
+    }
+
+    /// Opens the polynomial given by its evaluations at `point`, which may lie
+    /// inside or outside the domain, without interpolating it. The proof is the
+    /// same as [`Kzg::open`] on the interpolated polynomial.
+    pub fn open_lagrange(
+        powers: &LagrangePowers<E>,
+        evaluations: &[E::ScalarField],
+        point: E::ScalarField,
+    ) -> Result<Proof<E>, Error> {
+        check_evaluations(powers, evaluations)?;
+        let roots: Vec<E::ScalarField> = powers.domain.elements().collect();
+        let value = evaluate_in_evaluation_form(&roots, evaluations, point);
+        let quotient = quotient_in_evaluation_form(&roots, evaluations, point, value);
+        let w = E::G1::msm_unchecked(&powers.powers_of_g, &quotient);
+        Ok(Proof {
+            w: w.into_affine(),
+            random_v: None,
+        })
+    }
+}
+
+fn check_evaluations<E: Pairing>(
+    powers: &LagrangePowers<E>,
+    evaluations: &[E::ScalarField],
+) -> Result<(), Error> {
+    if evaluations.len() != powers.domain.size() {
+        return Err(Error::MismatchedLengths {
+            expected: powers.domain.size(),
+            found: evaluations.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Evaluates the polynomial taking `evaluations[i]` at `roots[i]` at `point`,
+/// where `roots` are the `n`-th roots of unity in any order, with the
+/// barycentric formula `(z^n - 1) / n * Σ f_i ω_i / (z - ω_i)`
+pub fn evaluate_in_evaluation_form<F: Field>(roots: &[F], evaluations: &[F], point: F) -> F {
+    if let Some(i) = roots.iter().position(|root| *root == point) {
+        return evaluations[i];
+    }
+
+    let mut denominators: Vec<F> = roots.iter().map(|root| point - root).collect();
+    batch_inversion(&mut denominators);
+    let sum: F = evaluations
+        .iter()
+        .zip(roots)
+        .zip(denominators)
+        .map(|((f, root), denominator)| *f * root * denominator)
+        .sum();
+    let n = roots.len() as u64;
+    sum * (point.pow([n]) - F::ONE) / F::from(n)
+}
+
+/// Evaluations of the quotient `q(X) = (f(X) - y) / (X - z)` on the roots.
+/// Where a root equals `z`, `q(z) = Σ_{ω_i ≠ z} (f_i - y) ω_i / (z (z - ω_i))`.
+pub fn quotient_in_evaluation_form<F: Field>(
+    roots: &[F],
+    evaluations: &[F],
+    point: F,
+    value: F,
+) -> Vec<F> {
+    let mut denominators: Vec<F> = roots.iter().map(|root| *root - point).collect();
+    batch_inversion(&mut denominators);
+    let mut quotient: Vec<F> = evaluations
+        .iter()
+        .zip(denominators)
+        .map(|(f, denominator)| (*f - value) * denominator)
+        .collect();
+
+    if let Some(m) = roots.iter().position(|root| *root == point) {
+        quotient[m] = evaluations
+            .iter()
+            .zip(roots)
+            .filter(|(_, root)| **root != point)
+            .map(|(f, root)| (*f - value) * root / (point * (point - root)))
+            .sum();
+    }
+    quotient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kzg10::UniPoly;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_poly::{DenseUVPolynomial, Polynomial};
+    use ark_std::{test_rng, UniformRand};
+
+    type Kzg381 = Kzg<Bls12_381>;
+
+    #[test]
+    fn test_commit_lagrange_equals_coefficient_form() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(63, rng).unwrap();
+        let (powers, _) = Kzg381::trim(&pp, 63).unwrap();
+
+        for size in [2, 8, 64] {
+            let lagrange = Kzg381::lagrange_powers(&powers, size).unwrap();
+            let evaluations: Vec<Fr> = (0..size).map(|_| Fr::rand(rng)).collect();
+            let poly =
+                UniPoly::<Bls12_381>::from_coefficients_vec(lagrange.domain.ifft(&evaluations));
+
+            let expected = Kzg381::commit(&powers, &poly).unwrap();
+            let commitment = Kzg381::commit_lagrange(&lagrange, &evaluations).unwrap();
+            assert_eq!(commitment, expected);
+        }
+    }
+
+    #[test]
+    fn test_open_lagrange_outside_domain() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(31, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 31).unwrap();
+        let lagrange = Kzg381::lagrange_powers(&powers, 32).unwrap();
+
+        let poly = UniPoly::<Bls12_381>::rand(31, rng);
+        let evaluations = lagrange.domain.fft(&poly.coeffs);
+        let commitment = Kzg381::commit_lagrange(&lagrange, &evaluations).unwrap();
+
+        let point = Fr::rand(rng);
+        let proof = Kzg381::open_lagrange(&lagrange, &evaluations, point).unwrap();
+        assert_eq!(proof, Kzg381::open(&powers, &poly, point).unwrap());
+        Kzg381::verify(&vk, &commitment, point, poly.evaluate(&point), &proof).unwrap();
+    }
+
+    #[test]
+    fn test_open_lagrange_inside_domain() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(15, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 15).unwrap();
+        let lagrange = Kzg381::lagrange_powers(&powers, 16).unwrap();
+
+        let evaluations: Vec<Fr> = (0..16).map(|_| Fr::rand(rng)).collect();
+        let commitment = Kzg381::commit_lagrange(&lagrange, &evaluations).unwrap();
+        for (i, point) in lagrange.domain.elements().enumerate() {
+            let proof = Kzg381::open_lagrange(&lagrange, &evaluations, point).unwrap();
+            Kzg381::verify(&vk, &commitment, point, evaluations[i], &proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_evaluate_in_evaluation_form() {
+        let rng = &mut test_rng();
+        let domain = Radix2EvaluationDomain::<Fr>::new(8).unwrap();
+        let poly = UniPoly::<Bls12_381>::rand(7, rng);
+        let roots: Vec<Fr> = domain.elements().collect();
+        let evaluations = domain.fft(&poly.coeffs);
+
+        let point = Fr::rand(rng);
+        assert_eq!(
+            evaluate_in_evaluation_form(&roots, &evaluations, point),
+            poly.evaluate(&point)
+        );
+        assert_eq!(
+            evaluate_in_evaluation_form(&roots, &evaluations, roots[3]),
+            evaluations[3]
+        );
+    }
+
+    #[test]
+    fn test_lagrange_errors() {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(7, rng).unwrap();
+        let (powers, _) = Kzg381::trim(&pp, 7).unwrap();
+        assert!(matches!(
+            Kzg381::lagrange_powers(&powers, 16),
+            Err(Error::UnsupportedDegree {
+                requested: 15,
+                supported: 7
+            })
+        ));
+
+        let lagrange = Kzg381::lagrange_powers(&powers, 8).unwrap();
+        assert!(matches!(
+            Kzg381::commit_lagrange(&lagrange, &[Fr::ONE; 4]),
+            Err(Error::MismatchedLengths {
+                expected: 8,
+                found: 4
+            })
+        ));
+    }
+}
@@ -0,0 +1,15 @@
+//! KZG10 polynomial commitments over any pairing-friendly curve.
+pub mod batch;
+pub mod ceremony;
+pub mod eip4844;
+pub mod error;
+pub mod kzg10;
+pub mod lagrange;
+pub mod shplonk;
+pub mod srs;
+pub mod transcript;
+
+pub use error::Error;
+pub use kzg10::{Commitment, Kzg, Powers, Proof, UniPoly, UniversalParams, VerifierKey};
+pub use lagrange::LagrangePowers;
+pub use transcript::Transcript;
@@ -0,0 +1,570 @@
+//! SHPLONK multi-open (BDFG20) following the structure of halo2's
+//! `ProverSHPLONK`/`VerifierSHPLONK`: queries are grouped into rotation sets
+//! of polynomials opened at the same points, `y` combines polynomials within a
+//! set, `v` combines sets (Horner order, first set gets the highest power) and
+//! `u` is the point at which the linearisation polynomial is opened. The proof
+//! is two G1 elements regardless of the number of polynomials and points.
+use std::collections::BTreeSet;
+
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, Polynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::batch::{interpolate, vanishing_polynomial};
+use crate::kzg10::{divide_by_linear, Commitment, Kzg, Powers, UniPoly, VerifierKey};
+use crate::transcript::Transcript;
+use crate::Error;
+
+/// Opening of `polynomials[poly]` at `point`
+#[derive(Clone, Copy, Debug)]
+pub struct ProverQuery<F> {
+    pub poly: usize,
+    pub point: F,
+}
+
+/// Claim that `commitments[commitment]` opens to `eval` at `point`
+#[derive(Clone, Copy, Debug)]
+pub struct VerifierQuery<F> {
+    pub commitment: usize,
+    pub point: F,
+    pub eval: F,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ShplonkProof<E: Pairing> {
+    /// Commitment to the combined quotient `h(X)`
+    pub w: E::G1Affine,
+    /// Commitment to the normalized linearisation quotient `L(X) / (X - u)`
+    pub w_prime: E::G1Affine,
+}
+
+/// Polynomials (by index) that are opened at exactly the same `points`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RotationSet<F> {
+    pub polys: Vec<usize>,
+    pub points: Vec<F>,
+}
+
+/// Groups queries the way halo2's `construct_intermediate_sets` does: first
+/// the point set of every polynomial, then the polynomials sharing a point
+/// set, both in order of first appearance. Also returns the union of points.
+pub fn construct_intermediate_sets<F: Field>(
+    queries: impl IntoIterator<Item = (usize, F)>,
+) -> (Vec<RotationSet<F>>, BTreeSet<F>) {
+    let mut super_point_set = BTreeSet::new();
+    let mut poly_point_sets: Vec<(usize, BTreeSet<F>)> = vec![];
+    for (poly, point) in queries {
+        super_point_set.insert(point);
+        match poly_point_sets.iter_mut().find(|(p, _)| *p == poly) {
+            Some((_, points)) => {
+                points.insert(point);
+            }
+            None => poly_point_sets.push((poly, BTreeSet::from([point]))),
+        }
+    }
+
+    let mut point_set_polys: Vec<(BTreeSet<F>, Vec<usize>)> = vec![];
+    for (poly, points) in poly_point_sets {
+        match point_set_polys.iter_mut().find(|(set, _)| *set == points) {
+            Some((_, polys)) => polys.push(poly),
+            None => point_set_polys.push((points, vec![poly])),
+        }
+    }
+
+    let rotation_sets = point_set_polys
+        .into_iter()
+        .map(|(points, polys)| RotationSet {
+            polys,
+            points: points.into_iter().collect(),
+        })
+        .collect();
+    (rotation_sets, super_point_set)
+}
+
+/// Rejects a query repeating an earlier (polynomial, point) pair: the
+/// verifier only checks the first claim of each pair
+fn check_distinct<F: Field>(queries: impl IntoIterator<Item = (usize, F)>) -> Result<(), Error> {
+    let mut seen = BTreeSet::new();
+    for query in queries {
+        if !seen.insert(query) {
+            return Err(Error::InvalidQuery);
+        }
+    }
+    Ok(())
+}
+
+/// `Π (u - z)` over `points`
+fn evaluate_vanishing_polynomial<'a, F: Field>(points: impl IntoIterator<Item = &'a F>, u: F) -> F {
+    points.into_iter().map(|z| u - z).product()
+}
+
+impl<E: Pairing> Kzg<E> {
+    /// Opens `polynomials` at all `queries` with a constant size proof. The
+    /// caller must have appended the commitments to `transcript` beforehand.
+    pub fn open_shplonk(
+        powers: &Powers<E>,
+        polynomials: &[UniPoly<E>],
+        queries: &[ProverQuery<E::ScalarField>],
+        transcript: &mut Transcript,
+    ) -> Result<ShplonkProof<E>, Error> {
+        if queries.is_empty() || queries.iter().any(|q| q.poly >= polynomials.len()) {
+            return Err(Error::InvalidQuery);
+        }
+        check_distinct(queries.iter().map(|q| (q.poly, q.point)))?;
+        for polynomial in polynomials {
+            Self::check_degree(polynomial, powers)?;
+        }
+        for query in queries {
+            let eval = polynomials[query.poly].evaluate(&query.point);
+            transcript.append(b"query", &(query.poly as u64, query.point, eval));
+        }
+
+        let (rotation_sets, super_point_set) =
+            construct_intermediate_sets(queries.iter().map(|q| (q.poly, q.point)));
+        let y: E::ScalarField = transcript.challenge(b"y");
+        let v: E::ScalarField = transcript.challenge(b"v");
+
+        // low degree equivalents R_ij(X) interpolating f_ij on the set's points
+        let low_degree_equivalents = rotation_sets
+            .iter()
+            .map(|set| {
+                set.polys
+                    .iter()
+                    .map(|&poly| {
+                        let evals: Vec<_> = set
+                            .points
+                            .iter()
+                            .map(|z| polynomials[poly].evaluate(z))
+                            .collect();
+                        interpolate(&set.points, &evals)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // h(X) = Σ_i v^(k-1-i) · Σ_j y^j (f_ij(X) - R_ij(X)) / Z_Si(X)
+        let mut h_x = DensePolynomial::zero();
+        for (set, r_xs) in rotation_sets.iter().zip(&low_degree_equivalents) {
+            let mut n_x = DensePolynomial::zero();
+            let mut power_of_y = E::ScalarField::ONE;
+            for (&poly, r_x) in set.polys.iter().zip(r_xs) {
+                n_x += (power_of_y, &(&polynomials[poly] - r_x));
+                power_of_y *= y;
+            }
+            let quotient = &n_x / &vanishing_polynomial(&set.points);
+            h_x = &(&h_x * v) + &quotient;
+        }
+        let w = E::G1::msm_unchecked(&powers.powers_of_g, &h_x.coeffs).into_affine();
+        transcript.append(b"w", &w);
+        let u: E::ScalarField = transcript.challenge(b"u");
+
+        // L(X) = Σ_i v^(k-1-i) · Z_{T\Si}(u) · Σ_j y^j (f_ij(X) - R_ij(u)) - Z_T(u) · h(X)
+        let mut l_x = DensePolynomial::zero();
+        let mut z_diffs = vec![];
+        for (set, r_xs) in rotation_sets.iter().zip(&low_degree_equivalents) {
+            let diffs = super_point_set.iter().filter(|z| !set.points.contains(z));
+            let z_diff = evaluate_vanishing_polynomial(diffs, u);
+
+            let mut l_i = DensePolynomial::zero();
+            let mut power_of_y = E::ScalarField::ONE;
+            for (&poly, r_x) in set.polys.iter().zip(r_xs) {
+                let r_eval = DensePolynomial::from_coefficients_slice(&[r_x.evaluate(&u)]);
+                l_i += (power_of_y, &(&polynomials[poly] - &r_eval));
+                power_of_y *= y;
+            }
+            l_x = &(&l_x * v) + &(&l_i * z_diff);
+            z_diffs.push(z_diff);
+        }
+        let z_t = evaluate_vanishing_polynomial(&super_point_set, u);
+        let l_x = &l_x - &(&h_x * z_t);
+        debug_assert!(l_x.evaluate(&u).is_zero());
+
+        // normalize by the first set's difference so the verifier saves a scalar multiplication
+        let z_0_diff_inverse = z_diffs[0].inverse().ok_or(Error::InvalidQuery)?;
+        let w_prime_x = &divide_by_linear(&l_x, u) * z_0_diff_inverse;
+        let w_prime = E::G1::msm_unchecked(&powers.powers_of_g, &w_prime_x.coeffs).into_affine();
+        transcript.append(b"w_prime", &w_prime);
+
+        Ok(ShplonkProof { w, w_prime })
+    }
+
+    /// Checks a proof produced by [`Kzg::open_shplonk`] against the claimed
+    /// evaluations with the single pairing check `e(F + u·W', H) = e(W', [τ]H)`
+    pub fn verify_shplonk(
+        vk: &VerifierKey<E>,
+        commitments: &[Commitment<E>],
+        queries: &[VerifierQuery<E::ScalarField>],
+        proof: &ShplonkProof<E>,
+        transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        if queries.is_empty() || queries.iter().any(|q| q.commitment >= commitments.len()) {
+            return Err(Error::InvalidQuery);
+        }
+        check_distinct(queries.iter().map(|q| (q.commitment, q.point)))?;
+        for query in queries {
+            transcript.append(
+                b"query",
+                &(query.commitment as u64, query.point, query.eval),
+            );
+        }
+        let get_eval = |commitment: usize, point: &E::ScalarField| {
+            queries
+                .iter()
+                .find(|q| q.commitment == commitment && q.point == *point)
+                .map(|q| q.eval)
+                .expect("rotation sets only contain queried points")
+        };
+
+        let (rotation_sets, super_point_set) =
+            construct_intermediate_sets(queries.iter().map(|q| (q.commitment, q.point)));
+        let y: E::ScalarField = transcript.challenge(b"y");
+        let v: E::ScalarField = transcript.challenge(b"v");
+        transcript.append(b"w", &proof.w);
+        let u: E::ScalarField = transcript.challenge(b"u");
+        transcript.append(b"w_prime", &proof.w_prime);
+
+        let mut outer = E::G1::zero();
+        let mut r_outer = E::ScalarField::zero();
+        let mut z_0 = E::ScalarField::zero();
+        let mut z_0_diff_inverse = E::ScalarField::zero();
+        for (i, set) in rotation_sets.iter().enumerate() {
+            let diffs = super_point_set.iter().filter(|z| !set.points.contains(z));
+            let mut z_diff = evaluate_vanishing_polynomial(diffs, u);
+            if i == 0 {
+                z_0 = evaluate_vanishing_polynomial(&set.points, u);
+                z_0_diff_inverse = z_diff.inverse().ok_or(Error::InvalidQuery)?;
+                z_diff = E::ScalarField::ONE;
+            } else {
+                z_diff *= z_0_diff_inverse;
+            }
+
+            let mut inner = E::G1::zero();
+            let mut r_inner = E::ScalarField::zero();
+            let mut power_of_y = E::ScalarField::ONE;
+            for &commitment in &set.polys {
+                let evals: Vec<_> = set.points.iter().map(|z| get_eval(commitment, z)).collect();
+                let r_x = interpolate(&set.points, &evals)?;
+                inner += commitments[commitment].0 * power_of_y;
+                r_inner += power_of_y * r_x.evaluate(&u);
+                power_of_y *= y;
+            }
+            outer = outer * v + inner * z_diff;
+            r_outer = r_outer * v + r_inner * z_diff;
+        }
+
+        let f = outer - vk.g * r_outer - proof.w * z_0 + proof.w_prime * u;
+        let check = E::multi_pairing([f, -proof.w_prime.into_group()], [vk.h()?, vk.beta_h()?]);
+        match check.is_zero() {
+            true => Ok(()),
+            false => Err(Error::InvalidProof),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+    use ark_std::{test_rng, UniformRand};
+
+    type Kzg381 = Kzg<Bls12_381>;
+
+    fn transcript(commitments: &[Commitment<Bls12_381>]) -> Transcript {
+        let mut transcript = Transcript::new(b"shplonk-test");
+        for commitment in commitments {
+            transcript.append(b"commitment", commitment);
+        }
+        transcript
+    }
+
+    type ToyInstance = (
+        Vec<UniPoly<Bls12_381>>,
+        Vec<ProverQuery<Fr>>,
+        Powers<Bls12_381>,
+        VerifierKey<Bls12_381>,
+    );
+
+    /// PLONK-like toy instance: evaluations at `x`, `ωx` and `ω^-1 x` as halo2
+    /// queries them for the current, next and previous rotation
+    fn toy_instance() -> ToyInstance {
+        let rng = &mut test_rng();
+        let pp = Kzg381::setup(16, rng).unwrap();
+        let (powers, vk) = Kzg381::trim(&pp, 16).unwrap();
+
+        let omega = Radix2EvaluationDomain::<Fr>::new(16).unwrap().group_gen();
+        let x = Fr::rand(rng);
+        let (cur, next, prev) = (x, x * omega, x / omega);
+
+        let polynomials = (0..4)
+            .map(|_| UniPoly::<Bls12_381>::rand(16, rng))
+            .collect();
+        let queries = [
+            (0, cur),
+            (0, next),
+            (1, cur),
+            (2, cur),
+            (2, next),
+            (3, prev),
+            (3, cur),
+            (3, next),
+        ]
+        .into_iter()
+        .map(|(poly, point)| ProverQuery { poly, point })
+        .collect();
+        (polynomials, queries, powers, vk)
+    }
+
+    fn verifier_queries(
+        polynomials: &[UniPoly<Bls12_381>],
+        queries: &[ProverQuery<Fr>],
+    ) -> Vec<VerifierQuery<Fr>> {
+        queries
+            .iter()
+            .map(|q| VerifierQuery {
+                commitment: q.poly,
+                point: q.point,
+                eval: polynomials[q.poly].evaluate(&q.point),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_intermediate_sets_match_halo2() {
+        // the example from halo2's `construct_intermediate_sets`:
+        // (C_0, {r_5}), (C_1, {r_1, r_2, r_3}), (C_2, {r_2, r_3, r_4}), (C_3, {r_2, r_3, r_4})
+        let r = |i: u64| Fr::from(i);
+        let queries = [
+            (0, r(5)),
+            (1, r(1)),
+            (2, r(2)),
+            (1, r(2)),
+            (3, r(4)),
+            (2, r(3)),
+            (1, r(3)),
+            (3, r(2)),
+            (2, r(4)),
+            (3, r(3)),
+        ];
+        let (sets, super_point_set) = construct_intermediate_sets(queries);
+
+        let expected = vec![
+            RotationSet {
+                polys: vec![0],
+                points: vec![r(5)],
+            },
+            RotationSet {
+                polys: vec![1],
+                points: vec![r(1), r(2), r(3)],
+            },
+            RotationSet {
+                polys: vec![2, 3],
+                points: vec![r(2), r(3), r(4)],
+            },
+        ];
+        assert_eq!(sets, expected);
+        assert_eq!(super_point_set, (1..=5).map(r).collect());
+    }
+
+    #[test]
+    fn test_shplonk_open_verify() {
+        let (polynomials, queries, powers, vk) = toy_instance();
+        let commitments: Vec<_> = polynomials
+            .iter()
+            .map(|f| Kzg381::commit(&powers, f).unwrap())
+            .collect();
+
+        let proof = Kzg381::open_shplonk(
+            &powers,
+            &polynomials,
+            &queries,
+            &mut transcript(&commitments),
+        )
+        .unwrap();
+        let verifier_queries = verifier_queries(&polynomials, &queries);
+        assert!(Kzg381::verify_shplonk(
+            &vk,
+            &commitments,
+            &verifier_queries,
+            &proof,
+            &mut transcript(&commitments)
+        )
+        .is_ok());
+
+        let vk = VerifierKey {
+            powers_of_h: vk.powers_of_h[..1].to_vec(),
+            ..vk
+        };
+        assert!(matches!(
+            Kzg381::verify_shplonk(
+                &vk,
+                &commitments,
+                &verifier_queries,
+                &proof,
+                &mut transcript(&commitments)
+            ),
+            Err(Error::InvalidSrs(_))
+        ));
+    }
+
+    #[test]
+    fn test_shplonk_rejects_tampered_eval() {
+        let (polynomials, queries, powers, vk) = toy_instance();
+        let commitments: Vec<_> = polynomials
+            .iter()
+            .map(|f| Kzg381::commit(&powers, f).unwrap())
+            .collect();
+        let proof = Kzg381::open_shplonk(
+            &powers,
+            &polynomials,
+            &queries,
+            &mut transcript(&commitments),
+        )
+        .unwrap();
+
+        for i in 0..queries.len() {
+            let mut verifier_queries = verifier_queries(&polynomials, &queries);
+            verifier_queries[i].eval += Fr::ONE;
+            assert!(Kzg381::verify_shplonk(
+                &vk,
+                &commitments,
+                &verifier_queries,
+                &proof,
+                &mut transcript(&commitments)
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_shplonk_rejects_swapped_commitment() {
+        let (polynomials, queries, powers, vk) = toy_instance();
+        let mut commitments: Vec<_> = polynomials
+            .iter()
+            .map(|f| Kzg381::commit(&powers, f).unwrap())
+            .collect();
+        let proof = Kzg381::open_shplonk(
+            &powers,
+            &polynomials,
+            &queries,
+            &mut transcript(&commitments),
+        )
+        .unwrap();
+
+        commitments.swap(0, 2);
+        let verifier_queries = verifier_queries(&polynomials, &queries);
+        assert!(Kzg381::verify_shplonk(
+            &vk,
+            &commitments,
+            &verifier_queries,
+            &proof,
+            &mut transcript(&commitments)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_shplonk_rejects_duplicate_query() {
+        let (polynomials, queries, powers, vk) = toy_instance();
+        let commitments: Vec<_> = polynomials
+            .iter()
+            .map(|f| Kzg381::commit(&powers, f).unwrap())
+            .collect();
+        let proof = Kzg381::open_shplonk(
+            &powers,
+            &polynomials,
+            &queries,
+            &mut transcript(&commitments),
+        )
+        .unwrap();
+
+        // a second, false claim about an opened (commitment, point) pair
+        let mut verifier_queries = verifier_queries(&polynomials, &queries);
+        let mut duplicate = verifier_queries[0];
+        duplicate.eval += Fr::ONE;
+        verifier_queries.push(duplicate);
+        assert!(matches!(
+            Kzg381::verify_shplonk(
+                &vk,
+                &commitments,
+                &verifier_queries,
+                &proof,
+                &mut transcript(&commitments)
+            ),
+            Err(Error::InvalidQuery)
+        ));
+
+        let mut prover_queries = queries.clone();
+        prover_queries.push(queries[0]);
+        assert!(matches!(
+            Kzg381::open_shplonk(
+                &powers,
+                &polynomials,
+                &prover_queries,
+                &mut transcript(&commitments)
+            ),
+            Err(Error::InvalidQuery)
+        ));
+    }
+
+    #[test]
+    fn test_linearisation_matches_unnormalized_paper_check() {
+        // BDFG20 without halo2's normalization: the verifier computes
+        // F' = Σ v^(k-1-i) Z_{T\Si}(u) (C_i - [r_i(u)]) - Z_T(u)·W and checks
+        // e(F', H) = e(W'', [τ - u]H) with W'' = Z_{T\S0}(u)·W'
+        let (polynomials, queries, powers, vk) = toy_instance();
+        let commitments: Vec<_> = polynomials
+            .iter()
+            .map(|f| Kzg381::commit(&powers, f).unwrap())
+            .collect();
+        let mut prover_transcript = transcript(&commitments);
+        let proof =
+            Kzg381::open_shplonk(&powers, &polynomials, &queries, &mut prover_transcript).unwrap();
+
+        let mut t = transcript(&commitments);
+        for query in verifier_queries(&polynomials, &queries) {
+            t.append(
+                b"query",
+                &(query.commitment as u64, query.point, query.eval),
+            );
+        }
+        let y: Fr = t.challenge(b"y");
+        let v: Fr = t.challenge(b"v");
+        t.append(b"w", &proof.w);
+        let u: Fr = t.challenge(b"u");
+
+        let (sets, super_point_set) =
+            construct_intermediate_sets(queries.iter().map(|q| (q.poly, q.point)));
+        let mut f = <Bls12_381 as Pairing>::G1::zero();
+        let mut z_0_diff = Fr::zero();
+        for (i, set) in sets.iter().enumerate() {
+            let diffs = super_point_set.iter().filter(|z| !set.points.contains(z));
+            let z_diff = evaluate_vanishing_polynomial(diffs, u);
+            if i == 0 {
+                z_0_diff = z_diff;
+            }
+            let mut inner = <Bls12_381 as Pairing>::G1::zero();
+            for (j, &poly) in set.polys.iter().enumerate() {
+                let evals: Vec<_> = set
+                    .points
+                    .iter()
+                    .map(|z| polynomials[poly].evaluate(z))
+                    .collect();
+                let r_u = interpolate(&set.points, &evals).unwrap().evaluate(&u);
+                inner += (commitments[poly].0 - vk.g * r_u) * y.pow([j as u64]);
+            }
+            f = f * v + inner * z_diff;
+        }
+        f -= proof.w * evaluate_vanishing_polynomial(&super_point_set, u);
+
+        let w = proof.w_prime * z_0_diff;
+        let tau_minus_u = vk.beta_h().unwrap().into_group() - vk.h().unwrap() * u;
+        assert_eq!(
+            Bls12_381::pairing(f, vk.h().unwrap()),
+            Bls12_381::pairing(w, tau_minus_u)
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Persisting and sharing structured reference strings: the crate's own
+//! `CanonicalSerialize` file format and an importer for snarkjs `.ptau`
+//! Powers-of-Tau transcripts.
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use ark_ec::pairing::Pairing;
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::{AffineRepr, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::batch::powers_of;
+use crate::kzg10::UniversalParams;
+use crate::transcript::Transcript;
+use crate::Error;
+
+impl<E: Pairing> UniversalParams<E> {
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.serialize_compressed(&mut writer)?;
+        Ok(())
+    }
+
+    /// Loads parameters written by [`UniversalParams::write_to_file`]. Points
+    /// are checked to be in the prime order subgroup and the powers are checked
+    /// to be consistent with a single `τ`.
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let pp = Self::deserialize_compressed(&mut reader)?;
+        pp.check_consistency()?;
+        Ok(pp)
+    }
+
+    /// Checks that the G1, Γ and G2 powers are successive powers of the same `τ`:
+    /// `e([τ^(i+1)]G1, H) = e([τ^i]G1, [τ]H)` (likewise for Γ) and
+    /// `e(G, [τ^(i+1)]H) = e([τ]G1, [τ^i]H)`, each batched over `i` with
+    /// powers of a challenge derived from the parameters themselves
+    pub fn check_consistency(&self) -> Result<(), Error> {
+        let (g, h) = (&self.powers_of_g, &self.powers_of_h);
+        if g.len() < 2 || h.len() < 2 {
+            return Err(Error::InvalidSrs("at least two powers are required"));
+        }
+        if g[0].is_zero() || h[0].is_zero() || g[1].is_zero() {
+            return Err(Error::InvalidSrs("generators and τ must be non-zero"));
+        }
+
+        let mut transcript = Transcript::new(b"srs-consistency");
+        transcript.append(b"powers_of_g", g);
+        transcript.append(b"powers_of_gamma_g", &self.powers_of_gamma_g);
+        transcript.append(b"powers_of_h", h);
+        let rho: E::ScalarField = transcript.challenge(b"rho");
+
+        let rhos = powers_of(rho, g.len() - 1);
+        let lower = E::G1::msm_unchecked(&g[..g.len() - 1], &rhos);
+        let upper = E::G1::msm_unchecked(&g[1..], &rhos);
+        let check = E::multi_pairing([upper, -lower], [h[0], h[1]]);
+        if !check.is_zero() {
+            return Err(Error::InvalidSrs("G1 powers are not consistent"));
+        }
+
+        let gamma_g = &self.powers_of_gamma_g;
+        if !gamma_g.is_empty() {
+            let rhos = powers_of(rho, gamma_g.len() - 1);
+            let lower = E::G1::msm_unchecked(&gamma_g[..gamma_g.len() - 1], &rhos);
+            let upper = E::G1::msm_unchecked(&gamma_g[1..], &rhos);
+            let check = E::multi_pairing([upper, -lower], [h[0], h[1]]);
+            if gamma_g.len() > g.len() || gamma_g[0].is_zero() || !check.is_zero() {
+                return Err(Error::InvalidSrs("powers of Γ are not consistent"));
+            }
+        }
+
+        let rhos = powers_of(rho, h.len() - 1);
+        let lower = E::G2::msm_unchecked(&h[..h.len() - 1], &rhos);
+        let upper = E::G2::msm_unchecked(&h[1..], &rhos);
+        let check = E::multi_pairing([g[0].into_group(), -g[1].into_group()], [upper, lower]);
+        if !check.is_zero() {
+            return Err(Error::InvalidSrs("G2 powers are not consistent"));
+        }
+        Ok(())
+    }
+}
+
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+const PTAU_HEADER: u32 = 1;
+const PTAU_TAU_G1: u32 = 2;
+const PTAU_TAU_G2: u32 = 3;
+
+/// Imports the tau sections of a snarkjs `.ptau` file, keeping the G1 and G2
+/// powers up to `max_degree` (a ptau of power `p` has `2^(p+1) - 1` G1 and
+/// `2^p` G2 powers). Coordinates are stored little-endian in Montgomery form.
+pub fn read_ptau<E, P1, P2, R>(
+    reader: &mut R,
+    max_degree: usize,
+) -> Result<UniversalParams<E>, Error>
+where
+    E: Pairing<G1Affine = Affine<P1>, G2Affine = Affine<P2>>,
+    P1: SWCurveConfig,
+    P2: SWCurveConfig,
+    R: Read + Seek,
+{
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != PTAU_MAGIC {
+        return Err(Error::InvalidSrs("not a ptau file"));
+    }
+    let _version = read_u32(reader)?;
+    let num_sections = read_u32(reader)?;
+
+    // (section type, offset, size)
+    let mut sections = vec![];
+    for _ in 0..num_sections {
+        let section_type = read_u32(reader)?;
+        let size = read_u64(reader)?;
+        let offset = reader.stream_position()?;
+        sections.push((section_type, offset, size));
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    let seek_section = |reader: &mut R, section_type: u32| -> Result<(), Error> {
+        let (_, offset, _) = sections
+            .iter()
+            .find(|(t, _, _)| *t == section_type)
+            .ok_or(Error::InvalidSrs("missing ptau section"))?;
+        reader.seek(SeekFrom::Start(*offset))?;
+        Ok(())
+    };
+
+    seek_section(reader, PTAU_HEADER)?;
+    // the header is untrusted, check it before sizing anything with it
+    let expected_modulus = <P1::BaseField as Field>::BasePrimeField::MODULUS.to_bytes_le();
+    let n8 = read_u32(reader)? as usize;
+    if n8 != expected_modulus.len() {
+        return Err(Error::InvalidSrs("ptau file is for a different curve"));
+    }
+    let mut modulus = vec![0u8; n8];
+    reader.read_exact(&mut modulus)?;
+    if modulus != expected_modulus {
+        return Err(Error::InvalidSrs("ptau file is for a different curve"));
+    }
+    let power = read_u32(reader)?;
+    let _ceremony_power = read_u32(reader)?;
+    if power >= usize::BITS - 1 {
+        return Err(Error::InvalidSrs("ptau power is too large"));
+    }
+
+    let num_g1 = max_degree.saturating_add(1).min((1 << (power + 1)) - 1);
+    let num_g2 = max_degree.saturating_add(1).min(1 << power);
+
+    seek_section(reader, PTAU_TAU_G1)?;
+    let montgomery = Montgomery::new(n8);
+    let powers_of_g = (0..num_g1)
+        .map(|_| read_point::<P1, R>(reader, &montgomery))
+        .collect::<Result<Vec<_>, _>>()?;
+    seek_section(reader, PTAU_TAU_G2)?;
+    let montgomery = Montgomery::new(n8);
+    let powers_of_h = (0..num_g2)
+        .map(|_| read_point::<P2, R>(reader, &montgomery))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pp = UniversalParams {
+        powers_of_g,
+        powers_of_gamma_g: vec![],
+        powers_of_h,
+    };
+    pp.check_consistency()?;
+    Ok(pp)
+}
+
+/// Converts little-endian Montgomery representations with `R = 2^(8·n8)`
+struct Montgomery<F: PrimeField> {
+    n8: usize,
+    r_inverse: F,
+}
+
+impl<F: PrimeField> Montgomery<F> {
+    fn new(n8: usize) -> Self {
+        let r = F::from(2u64).pow([8 * n8 as u64]);
+        Montgomery {
+            n8,
+            r_inverse: r.inverse().expect("R is a power of two and non-zero"),
+        }
+    }
+
+    fn read(&self, reader: &mut impl Read) -> Result<F, Error> {
+        let mut bytes = vec![0u8; self.n8];
+        reader.read_exact(&mut bytes)?;
+        Ok(F::from_le_bytes_mod_order(&bytes) * self.r_inverse)
+    }
+}
+
+fn read_point<P, R>(
+    reader: &mut R,
+    montgomery: &Montgomery<<P::BaseField as Field>::BasePrimeField>,
+) -> Result<Affine<P>, Error>
+where
+    P: SWCurveConfig,
+    R: Read,
+{
+    let mut read_coordinate = || -> Result<P::BaseField, Error> {
+        let elems = (0..P::BaseField::extension_degree())
+            .map(|_| montgomery.read(reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(P::BaseField::from_base_prime_field_elems(elems).expect("extension degree matches"))
+    };
+    let x = read_coordinate()?;
+    let y = read_coordinate()?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(Affine::identity());
+    }
+
+    let point = Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::InvalidSrs(
+            "ptau point is not in the prime order subgroup",
+        ));
+    }
+    Ok(point)
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, Error> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, Error> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kzg, UniPoly};
+    use ark_bn254::{Bn254, Fq, Fr, G1Affine};
+    use ark_poly::{DenseUVPolynomial, Polynomial};
+    use ark_std::{test_rng, UniformRand};
+    use std::io::{Cursor, Write};
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ark-kzg-{}-{}", std::process::id(), name))
+    }
+
+    fn to_montgomery(value: Fq) -> Vec<u8> {
+        let r = Fq::from(2u64).pow([256u64]);
+        (value * r).into_bigint().to_bytes_le()
+    }
+
+    /// Writes the header and tau sections of a BN254 ptau file of the given power
+    fn write_ptau(pp: &UniversalParams<Bn254>, power: u32) -> Vec<u8> {
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend(Fq::MODULUS.to_bytes_le());
+        header.extend(power.to_le_bytes());
+        header.extend(power.to_le_bytes());
+        write_ptau_with_header(pp, power, header)
+    }
+
+    fn write_ptau_with_header(pp: &UniversalParams<Bn254>, power: u32, header: Vec<u8>) -> Vec<u8> {
+        let g1: Vec<u8> = pp.powers_of_g[..(1 << (power + 1)) - 1]
+            .iter()
+            .flat_map(|p| [to_montgomery(p.x), to_montgomery(p.y)].concat())
+            .collect();
+        let g2: Vec<u8> = pp.powers_of_h[..1 << power]
+            .iter()
+            .flat_map(|p| {
+                [p.x.c0, p.x.c1, p.y.c0, p.y.c1]
+                    .into_iter()
+                    .flat_map(to_montgomery)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut file = Cursor::new(vec![]);
+        file.write_all(PTAU_MAGIC).unwrap();
+        file.write_all(&1u32.to_le_bytes()).unwrap();
+        file.write_all(&3u32.to_le_bytes()).unwrap();
+        // sections in non-canonical order, the reader must look them up by type
+        for (section_type, data) in [(PTAU_TAU_G2, g2), (PTAU_HEADER, header), (PTAU_TAU_G1, g1)] {
+            file.write_all(&section_type.to_le_bytes()).unwrap();
+            file.write_all(&(data.len() as u64).to_le_bytes()).unwrap();
+            file.write_all(&data).unwrap();
+        }
+        file.into_inner()
+    }
+
+    #[test]
+    fn test_file_roundtrip() {
+        let rng = &mut test_rng();
+        let pp = Kzg::<Bn254>::setup(16, rng).unwrap();
+        let path = temp_path("roundtrip.srs");
+        pp.write_to_file(&path).unwrap();
+        let loaded = UniversalParams::<Bn254>::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(pp, loaded);
+    }
+
+    #[test]
+    fn test_inconsistent_g1_powers() {
+        let rng = &mut test_rng();
+        let mut pp = Kzg::<Bn254>::setup(8, rng).unwrap();
+        pp.powers_of_g.swap(3, 4);
+        assert!(matches!(
+            pp.check_consistency(),
+            Err(Error::InvalidSrs("G1 powers are not consistent"))
+        ));
+    }
+
+    #[test]
+    fn test_inconsistent_gamma_powers() {
+        let rng = &mut test_rng();
+        let mut pp = Kzg::<Bn254>::setup(8, rng).unwrap();
+        pp.powers_of_gamma_g[2] = pp.powers_of_g[2];
+        assert!(matches!(
+            pp.check_consistency(),
+            Err(Error::InvalidSrs("powers of Γ are not consistent"))
+        ));
+    }
+
+    #[test]
+    fn test_inconsistent_g2_powers() {
+        let rng = &mut test_rng();
+        let mut pp = Kzg::<Bn254>::setup(8, rng).unwrap();
+        let other = Kzg::<Bn254>::setup(8, rng).unwrap();
+        pp.powers_of_h[5] = other.powers_of_h[5];
+        assert!(matches!(
+            pp.check_consistency(),
+            Err(Error::InvalidSrs("G2 powers are not consistent"))
+        ));
+    }
+
+    #[test]
+    fn test_montgomery_generator() {
+        // snarkjs representation of the BN254 G1 generator (1, 2)
+        let bytes = [
+            "9d0d8fc58d435dd33d0bc7f528eb780a2c4679786fa36e662fdf079ac1770a0e",
+            "3a1b1e8b1b87baa67b168eeb51d6f114588cf2f0de46ddcc5ebe0f3483ef141c",
+        ]
+        .map(|hex| {
+            (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .concat();
+        let point =
+            read_point::<ark_bn254::g1::Config, _>(&mut &bytes[..], &Montgomery::new(32)).unwrap();
+        assert_eq!(point, G1Affine::generator());
+    }
+
+    #[test]
+    fn test_read_ptau() {
+        let tau = Fr::rand(&mut test_rng());
+        let power = 3;
+        let pp = Kzg::<Bn254>::setup_from_tau((1 << (power + 1)) - 2, tau).unwrap();
+        let ptau = write_ptau(&pp, power);
+
+        let loaded = read_ptau::<Bn254, _, _, _>(&mut Cursor::new(&ptau), 1 << 10).unwrap();
+        assert_eq!(loaded.powers_of_g, pp.powers_of_g);
+        assert_eq!(loaded.powers_of_h, pp.powers_of_h[..1 << power]);
+
+        let trimmed = read_ptau::<Bn254, _, _, _>(&mut Cursor::new(&ptau), 5).unwrap();
+        assert_eq!(trimmed.powers_of_g, pp.powers_of_g[..6]);
+        assert_eq!(trimmed.powers_of_h, pp.powers_of_h[..6]);
+    }
+
+    #[test]
+    fn test_ptau_srs_commits_and_opens() {
+        let rng = &mut test_rng();
+        let power = 4;
+        let pp = Kzg::<Bn254>::setup((1 << (power + 1)) - 2, rng).unwrap();
+        let ptau = write_ptau(&pp, power);
+        let loaded = read_ptau::<Bn254, _, _, _>(&mut Cursor::new(&ptau), 20).unwrap();
+
+        let (powers, vk) = Kzg::trim(&loaded, 20).unwrap();
+        let f = UniPoly::<Bn254>::rand(20, rng);
+        let comm = Kzg::commit(&powers, &f).unwrap();
+        let points = [Fr::rand(rng), Fr::rand(rng)];
+        let values = points.map(|z| f.evaluate(&z));
+        let proof = Kzg::open_multi_point(&powers, &f, &points).unwrap();
+        assert!(Kzg::verify_multi_point(&vk, &comm, &points, &values, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_ptau_wrong_curve() {
+        let rng = &mut test_rng();
+        let pp = Kzg::<Bn254>::setup(6, rng).unwrap();
+        let ptau = write_ptau(&pp, 2);
+        assert!(matches!(
+            read_ptau::<ark_bls12_381::Bls12_381, _, _, _>(&mut Cursor::new(&ptau), 6),
+            Err(Error::InvalidSrs("ptau file is for a different curve"))
+        ));
+    }
+
+    #[test]
+    fn test_ptau_corrupted_header() {
+        let rng = &mut test_rng();
+        let pp = Kzg::<Bn254>::setup(6, rng).unwrap();
+        let header = |n8: u32, power: u32| {
+            let mut header = n8.to_le_bytes().to_vec();
+            header.extend(Fq::MODULUS.to_bytes_le());
+            header.extend(power.to_le_bytes());
+            header.extend(power.to_le_bytes());
+            header
+        };
+        let read = |n8: u32, power: u32| {
+            let ptau = write_ptau_with_header(&pp, 2, header(n8, power));
+            read_ptau::<Bn254, _, _, _>(&mut Cursor::new(&ptau), usize::MAX)
+        };
+
+        // a length of 4 GiB is rejected before anything is allocated
+        assert!(matches!(
+            read(u32::MAX, 2),
+            Err(Error::InvalidSrs("ptau file is for a different curve"))
+        ));
+        assert!(matches!(
+            read(48, 2),
+            Err(Error::InvalidSrs("ptau file is for a different curve"))
+        ));
+        for power in [usize::BITS - 1, 63, u32::MAX] {
+            assert!(matches!(
+                read(32, power),
+                Err(Error::InvalidSrs("ptau power is too large"))
+            ));
+        }
+        assert!(read(32, 2).is_ok());
+    }
+}
@@ -0,0 +1,63 @@
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+
+/// Fiat-Shamir transcript: every message and challenge label is absorbed into
+/// a running SHA-256 state, challenges are squeezed from it
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Sha256,
+}
+
+impl Transcript {
+    pub fn new(label: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(label);
+        Transcript { hasher }
+    }
+
+    pub fn append<T: CanonicalSerialize + ?Sized>(&mut self, label: &[u8], message: &T) {
+        let mut bytes = vec![];
+        message
+            .serialize_compressed(&mut bytes)
+            .expect("serializing into a vector cannot fail");
+        self.hasher.update(label);
+        self.hasher.update(&bytes);
+    }
+
+    pub fn challenge<F: PrimeField>(&mut self, label: &[u8]) -> F {
+        self.hasher.update(label);
+        // 64 bytes keep the modular reduction bias negligible
+        let low = self.hasher.clone().chain_update([0u8]).finalize();
+        let high = self.hasher.clone().chain_update([1u8]).finalize();
+        self.hasher.update(low);
+
+        let mut bytes = low.to_vec();
+        bytes.extend_from_slice(&high);
+        F::from_le_bytes_mod_order(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_challenges_depend_on_messages() {
+        let mut a = Transcript::new(b"test");
+        let mut b = Transcript::new(b"test");
+        a.append(b"m", &Fr::from(1u64));
+        b.append(b"m", &Fr::from(2u64));
+        assert_ne!(a.challenge::<Fr>(b"c"), b.challenge::<Fr>(b"c"));
+    }
+
+    #[test]
+    fn test_consecutive_challenges_differ() {
+        let mut t = Transcript::new(b"test");
+        let first: Fr = t.challenge(b"c");
+        let second: Fr = t.challenge(b"c");
+        assert_ne!(first, second);
+        assert_eq!(first, Transcript::new(b"test").challenge::<Fr>(b"c"));
+    }
+}
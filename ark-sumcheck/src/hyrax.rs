@@ -0,0 +1,200 @@
+//! Hyrax commitments to multilinear polynomials (Thaler's Chp. 14.3).
+//!
+//! The `2^n` evaluations of an `n`-variate multilinear polynomial are laid out
+//! as a `2^(n - n/2) x 2^(n/2)` matrix `M` and every row is committed with a
+//! Pedersen vector commitment. Since `f(r) = L(r)^T * M * R(r)`, where `L` and
+//! `R` are the tensors of Lagrange basis evaluations for the high and low
+//! variables, an opening only needs to reveal the row combination `L^T * M`,
+//! which the verifier checks homomorphically against the row commitments.
+//!
+//! This is the plain (not zero-knowledge) variant without the inner product
+//! argument, so commitments and proofs both have size `O(sqrt(2^n))`.
+use ark_ec::CurveGroup;
+use ark_ff::{Field, Zero};
+use ark_poly::DenseMultilinearExtension;
+use ark_std::rand::Rng;
+
+/// Pedersen generators, one per column of the evaluation matrix.
+pub struct HyraxParams<G: CurveGroup> {
+    num_vars: usize,
+    generators: Vec<G::Affine>,
+}
+
+/// One Pedersen commitment per row of the evaluation matrix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyraxCommitment<G: CurveGroup> {
+    pub row_commitments: Vec<G::Affine>,
+}
+
+/// The row combination `L(r)^T * M` for the opened point `r`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyraxProof<F: Field> {
+    pub combined_row: Vec<F>,
+}
+
+impl<G: CurveGroup> HyraxParams<G> {
+    /// Samples the generators for polynomials in `num_vars` variables.
+    pub fn setup<R: Rng>(num_vars: usize, rng: &mut R) -> Self {
+        let (_, num_columns) = dimensions(num_vars);
+        let generators = (0..num_columns).map(|_| G::rand(rng)).collect::<Vec<_>>();
+        HyraxParams {
+            num_vars,
+            generators: G::normalize_batch(&generators),
+        }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    pub fn commit(&self, poly: &DenseMultilinearExtension<G::ScalarField>) -> HyraxCommitment<G> {
+        assert_eq!(poly.num_vars, self.num_vars, "Invalid number of variables");
+
+        let row_commitments = poly
+            .evaluations
+            .chunks(self.generators.len())
+            .map(|row| G::msm_unchecked(&self.generators, row))
+            .collect::<Vec<_>>();
+        HyraxCommitment {
+            row_commitments: G::normalize_batch(&row_commitments),
+        }
+    }
+
+    pub fn open(
+        &self,
+        poly: &DenseMultilinearExtension<G::ScalarField>,
+        point: &[G::ScalarField],
+    ) -> HyraxProof<G::ScalarField> {
+        assert_eq!(poly.num_vars, self.num_vars, "Invalid number of variables");
+        assert_eq!(point.len(), self.num_vars, "Invalid point dimension");
+
+        let num_columns = self.generators.len();
+        let (_, high) = point.split_at(self.num_vars / 2);
+        let left = eq_evaluations(high);
+
+        let mut combined_row = vec![G::ScalarField::zero(); num_columns];
+        for (row, l) in poly.evaluations.chunks(num_columns).zip(left) {
+            for (acc, m) in combined_row.iter_mut().zip(row) {
+                *acc += l * m;
+            }
+        }
+        HyraxProof { combined_row }
+    }
+
+    /// Checks that the committed polynomial evaluates to `value` at `point`.
+    pub fn verify(
+        &self,
+        commitment: &HyraxCommitment<G>,
+        point: &[G::ScalarField],
+        value: G::ScalarField,
+        proof: &HyraxProof<G::ScalarField>,
+    ) -> bool {
+        let (num_rows, num_columns) = dimensions(self.num_vars);
+        if point.len() != self.num_vars
+            || commitment.row_commitments.len() != num_rows
+            || proof.combined_row.len() != num_columns
+        {
+            return false;
+        }
+
+        let (low, high) = point.split_at(self.num_vars / 2);
+        let left = eq_evaluations(high);
+        let right = eq_evaluations(low);
+
+        let combined_commitment = G::msm_unchecked(&commitment.row_commitments, &left);
+        let combined_row_commitment = G::msm_unchecked(&self.generators, &proof.combined_row);
+        let combined_value: G::ScalarField = proof
+            .combined_row
+            .iter()
+            .zip(&right)
+            .map(|(w, r)| *w * r)
+            .sum();
+
+        combined_commitment == combined_row_commitment && combined_value == value
+    }
+}
+
+/// Returns the number of rows and columns of the evaluation matrix. The
+/// `num_vars / 2` low variables index the columns.
+fn dimensions(num_vars: usize) -> (usize, usize) {
+    (1 << (num_vars - num_vars / 2), 1 << (num_vars / 2))
+}
+
+/// Evaluates the Lagrange basis polynomials of the boolean hypercube at
+/// `point`, where the i-th entry corresponds to the vertex whose j-th variable
+/// is the j-th bit of i.
+pub fn eq_evaluations<F: Field>(point: &[F]) -> Vec<F> {
+    let mut evaluations = vec![F::ONE];
+    for r in point {
+        let upper = evaluations.iter().map(|e| *e * r).collect::<Vec<_>>();
+        for (e, u) in evaluations.iter_mut().zip(&upper) {
+            *e -= u;
+        }
+        evaluations.extend(upper);
+    }
+    evaluations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_poly::MultilinearExtension;
+    use ark_std::{test_rng, UniformRand};
+    use ark_test_curves::bls12_381::{Fr, G1Projective};
+
+    type Params = HyraxParams<G1Projective>;
+
+    fn random_poly<R: Rng>(num_vars: usize, rng: &mut R) -> DenseMultilinearExtension<Fr> {
+        DenseMultilinearExtension::rand(num_vars, rng)
+    }
+
+    #[test]
+    fn test_eq_evaluations_on_hypercube() {
+        let point = vec![Fr::ONE, Fr::ZERO, Fr::ONE];
+        let evaluations = eq_evaluations(&point);
+        assert_eq!(evaluations.len(), 8);
+        for (i, e) in evaluations.iter().enumerate() {
+            assert_eq!(*e, if i == 0b101 { Fr::ONE } else { Fr::ZERO });
+        }
+    }
+
+    #[test]
+    fn test_open_and_verify() {
+        let mut rng = test_rng();
+        for num_vars in 1..8 {
+            let params = Params::setup(num_vars, &mut rng);
+            let poly = random_poly(num_vars, &mut rng);
+            let commitment = params.commit(&poly);
+            let point = (0..num_vars)
+                .map(|_| Fr::rand(&mut rng))
+                .collect::<Vec<_>>();
+            let value = poly.evaluate(&point).unwrap();
+            let proof = params.open(&poly, &point);
+            assert!(params.verify(&commitment, &point, value, &proof));
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_value() {
+        let mut rng = test_rng();
+        let params = Params::setup(5, &mut rng);
+        let poly = random_poly(5, &mut rng);
+        let commitment = params.commit(&poly);
+        let point = (0..5).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let value = poly.evaluate(&point).unwrap();
+        let proof = params.open(&poly, &point);
+        assert!(!params.verify(&commitment, &point, value + Fr::ONE, &proof));
+    }
+
+    #[test]
+    fn test_verify_rejects_other_polynomial() {
+        let mut rng = test_rng();
+        let params = Params::setup(4, &mut rng);
+        let poly = random_poly(4, &mut rng);
+        let other = random_poly(4, &mut rng);
+        let commitment = params.commit(&poly);
+        let point = (0..4).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let proof = params.open(&other, &point);
+        assert!(!params.verify(&commitment, &point, other.evaluate(&point).unwrap(), &proof));
+    }
+}
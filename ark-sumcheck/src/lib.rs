@@ -1,3 +1,4 @@
+pub mod hyrax;
 pub mod polynomial;
 pub mod solomon_reed_code;
 pub mod sumcheck;
@@ -2,7 +2,7 @@ use ark_ff::Field;
 use ark_poly::{
     multivariate::{SparsePolynomial, SparseTerm, Term},
     univariate::SparsePolynomial as UnivariatePolynomial,
-    DenseMVPolynomial,
+    DenseMVPolynomial, DenseMultilinearExtension, Polynomial,
 };
 use itertools::{Either, Itertools};
 use std::collections::HashSet;
@@ -85,12 +85,31 @@ pub fn cast_mv_to_uv_polynomial<F: Field>(
     }
     UnivariatePolynomial::from_coefficients_vec(univariate_terms)
 }
+/// Evaluates the polynomial over {0, 1}^n, the i-th evaluation being at the
+/// point whose j-th variable is the j-th bit of i. The result is the
+/// multilinear extension of the polynomial, which is the polynomial itself
+/// whenever every variable has degree at most one.
+pub fn multilinear_extension<F: Field>(
+    polynomial: &SparsePolynomial<F, SparseTerm>,
+) -> DenseMultilinearExtension<F> {
+    let num_vars = polynomial.num_vars;
+    let evaluations = (0..1usize << num_vars)
+        .map(|i| {
+            let point: Vec<F> = (0..num_vars)
+                .map(|j| if (i >> j) & 1 == 1 { F::ONE } else { F::ZERO })
+                .collect();
+            polynomial.evaluate(&point)
+        })
+        .collect();
+    DenseMultilinearExtension::from_evaluations_vec(num_vars, evaluations)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use ark_ff::fields::{Fp64, MontBackend, MontConfig};
     use ark_poly::multivariate::SparseTerm;
-    use ark_poly::DenseMVPolynomial;
+    use ark_poly::{DenseMVPolynomial, MultilinearExtension};
 
     #[derive(MontConfig)]
     #[modulus = "17"]
@@ -155,4 +174,21 @@ mod test {
         let poly_reduced = assign_values(&poly, values);
         assert_eq!(should, poly_reduced);
     }
+
+    #[test]
+    fn test_multilinear_extension() {
+        // x_0*x_2 + x_1*x_2 agrees with its multilinear extension everywhere
+        let poly = SparsePolynomial::from_coefficients_vec(
+            3,
+            vec![
+                (F17::from(1), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                (F17::from(1), SparseTerm::new(vec![(1, 1), (2, 1)])),
+            ],
+        );
+        let mle = multilinear_extension(&poly);
+        assert_eq!(mle.evaluations[0b101], F17::from(1));
+        assert_eq!(mle.evaluations[0b111], F17::from(2));
+        let point = vec![F17::from(2), F17::from(5), F17::from(7)];
+        assert_eq!(mle.evaluate(&point).unwrap(), poly.evaluate(&point));
+    }
 }
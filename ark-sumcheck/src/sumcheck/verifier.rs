@@ -8,7 +8,7 @@ use ark_std::test_rng;
 
 pub struct VerifierState<F: Field> {
     solution: F,
-    poly: SparsePolynomial<F, SparseTerm>,
+    poly: Option<SparsePolynomial<F, SparseTerm>>,
     total_rounds: usize,
     actual_round: usize,
     running_poly: UnivariatePolynomial<F>,
@@ -20,7 +20,7 @@ impl<F: Field> VerifierState<F> {
         let total_rounds = poly.num_vars;
         VerifierState {
             solution: result,
-            poly,
+            poly: Some(poly),
             running_poly: UnivariatePolynomial::<F>::zero(),
             total_rounds,
             actual_round: 0,
@@ -28,6 +28,21 @@ impl<F: Field> VerifierState<F> {
         }
     }
 
+    /// Creates a verifier that never sees the polynomial. Instead of
+    /// evaluating it after the last round, the caller checks the claim
+    /// returned by [`VerifierState::final_claim`] against a commitment,
+    /// e.g. with [`crate::hyrax::HyraxParams::verify`].
+    pub fn new_committed(result: F, num_vars: usize) -> Self {
+        VerifierState {
+            solution: result,
+            poly: None,
+            running_poly: UnivariatePolynomial::<F>::zero(),
+            total_rounds: num_vars,
+            actual_round: 0,
+            rs: Vec::with_capacity(num_vars),
+        }
+    }
+
     pub fn get_total_rounds(&self) -> usize {
         self.total_rounds
     }
@@ -36,6 +51,16 @@ impl<F: Field> VerifierState<F> {
         self.actual_round
     }
 
+    /// Once every round has been verified, returns the point and the value
+    /// the polynomial is claimed to take there.
+    pub fn final_claim(&self) -> Option<(&[F], F)> {
+        if self.actual_round < self.total_rounds {
+            return None;
+        }
+        let value = self.running_poly.evaluate(self.rs.last()?);
+        Some((&self.rs, value))
+    }
+
     pub fn verify_round(&mut self, round_poly: UnivariatePolynomial<F>) -> F {
         // TODO: Improve Error handling with result
         assert!(
@@ -59,10 +84,9 @@ impl<F: Field> VerifierState<F> {
         self.running_poly = round_poly;
 
         if self.actual_round == self.total_rounds {
-            assert_eq!(
-                self.running_poly.evaluate(&field),
-                self.poly.evaluate(&self.rs)
-            );
+            if let Some(poly) = &self.poly {
+                assert_eq!(self.running_poly.evaluate(&field), poly.evaluate(&self.rs));
+            }
         }
 
         field
@@ -131,7 +155,7 @@ mod tests {
         let mut verifier = VerifierState {
             total_rounds: 3,
             actual_round: 1,
-            poly,
+            poly: Some(poly),
             rs: vec![rand_field],
             solution: F17::from(12),
             running_poly: UnivariatePolynomial::from_coefficients_vec(vec![
@@ -161,11 +185,47 @@ mod tests {
             total_rounds: 3,
             actual_round: 2,
             running_poly: s2,
-            poly,
+            poly: Some(poly),
             solution: F17::from(12),
             rs,
         };
 
         verifier.verify_round(s3);
     }
+
+    #[test]
+    fn test_committed_sumcheck() {
+        use crate::hyrax::HyraxParams;
+        use crate::polynomial::multilinear_extension;
+        use ark_test_curves::bls12_381::{Fr, G1Projective};
+
+        // 3*x_0*x_1 + x_1*x_2*x_3 + 5*x_3 + 2
+        let poly = SparsePolynomial::from_coefficients_vec(
+            4,
+            vec![
+                (Fr::from(3), SparseTerm::new(vec![(0, 1), (1, 1)])),
+                (Fr::from(1), SparseTerm::new(vec![(1, 1), (2, 1), (3, 1)])),
+                (Fr::from(5), SparseTerm::new(vec![(3, 1)])),
+                (Fr::from(2), SparseTerm::new(vec![])),
+            ],
+        );
+        let params = HyraxParams::<G1Projective>::setup(4, &mut test_rng());
+        let mle = multilinear_extension(&poly);
+        let commitment = params.commit(&mle);
+
+        let mut prover = ProverState::new(poly);
+        let mut verifier = VerifierState::new_committed(prover.calculate_sum(), 4);
+        assert!(verifier.final_claim().is_none());
+        for round in 0..4 {
+            let r = verifier.verify_round(prover.calculate_round_poly());
+            if round < 3 {
+                prover.update_random_vars(r);
+            }
+        }
+
+        let (point, value) = verifier.final_claim().unwrap();
+        let proof = params.open(&mle, point);
+        assert!(params.verify(&commitment, point, value, &proof));
+        assert!(!params.verify(&commitment, point, value + Fr::ONE, &proof));
+    }
 }
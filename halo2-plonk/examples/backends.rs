@@ -0,0 +1,52 @@
+use std::time::Instant;
+
+use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+
+use plonk_example::backend::{Backend, Ipa, Kzg};
+use plonk_example::cost::CostModel;
+use plonk_example::*;
+
+const L: usize = 2;
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+// Compares the trusted-setup and transparent backends on the same circuit:
+//   cargo run --release --example backends
+fn run<B: Backend>() {
+    let message = [B::Scalar::from(475023450948321098), B::Scalar::from(42)];
+    let commitment =
+        poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+            .hash(message);
+    let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L, B::Scalar>::new(message);
+
+    let start = Instant::now();
+    let params = B::setup(CostModel::measure(&circuit).unwrap().min_k);
+    let pk = B::keygen(&params, &circuit).unwrap();
+    let setup_time = start.elapsed();
+
+    let start = Instant::now();
+    let proof = B::prove(&params, &pk, circuit, &[commitment]).unwrap();
+    let prover_time = start.elapsed();
+
+    let start = Instant::now();
+    B::verify(&params, pk.get_vk(), &[commitment], &proof).unwrap();
+    let verifier_time = start.elapsed();
+
+    println!(
+        "{:<12} | {:>10} | {:>12.2?} | {:>12.2?} | {:>12.2?}",
+        B::NAME,
+        proof.len(),
+        setup_time,
+        prover_time,
+        verifier_time
+    );
+}
+
+pub fn main() {
+    println!(
+        "{:<12} | {:>10} | {:>12} | {:>12} | {:>12}",
+        "backend", "proof (B)", "setup", "prover", "verifier"
+    );
+    run::<Kzg>();
+    run::<Ipa>();
+}
@@ -0,0 +1,54 @@
+use std::time::Instant;
+
+use halo2curves::bn256::Fr;
+
+use plonk_example::aggregation::verify_batch;
+use plonk_example::backend::{Backend, Kzg};
+use plonk_example::cost::CostModel;
+use plonk_example::merkle::{MerkleCircuit, MerkleTree};
+use plonk_example::*;
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+const DEPTH: usize = 8;
+const PROOFS: usize = 16;
+
+type Spec = MySpec<WIDTH, RATE>;
+type MembershipCircuit = MerkleCircuit<Spec, WIDTH, RATE, DEPTH>;
+
+// Verifies membership proofs one by one, each with its own pairing check, and
+// then as a batch with a single one:
+//   cargo run --release --example batch_verify
+pub fn main() {
+    let leaves = (0..PROOFS as u64).map(Fr::from).collect::<Vec<_>>();
+    let tree = MerkleTree::<Spec, WIDTH, RATE>::new(DEPTH, &leaves);
+    let root = [tree.root()];
+
+    let circuits = (0..PROOFS)
+        .map(|i| MembershipCircuit::new(tree.leaf(i), &tree.path(i)))
+        .collect::<Vec<_>>();
+    let k = CostModel::measure(&circuits[0]).unwrap().min_k;
+    let params = Kzg::setup(k);
+    let pk = Kzg::keygen(&params, &circuits[0]).unwrap();
+    let vk = pk.get_vk();
+    let proofs = circuits
+        .into_iter()
+        .map(|circuit| Kzg::prove(&params, &pk, circuit, &root).unwrap())
+        .collect::<Vec<_>>();
+    println!("{} membership proofs, K = {}", PROOFS, k);
+
+    let start = Instant::now();
+    for proof in &proofs {
+        Kzg::verify(&params, vk, &root, proof).unwrap();
+    }
+    println!("one by one: {:>10.2?}", start.elapsed());
+
+    let start = Instant::now();
+    verify_batch(
+        &params,
+        vk,
+        proofs.iter().map(|proof| (&root[..], &proof[..])),
+    )
+    .unwrap();
+    println!("batched:    {:>10.2?}", start.elapsed());
+}
@@ -0,0 +1,58 @@
+use halo2_proofs::dev::CircuitLayout;
+
+use halo2curves::bn256::Fr;
+
+use plotters::prelude::*;
+
+use plonk_example::cost::CostModel;
+use plonk_example::*;
+
+const L: usize = 2;
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+// Prints the cost model of `HashCircuit` and renders its layout in the
+// smallest table it fits in:
+//   cargo run --example circuit_layout --features dev-graph [-- layout.svg]
+pub fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "hash-circuit-layout.svg".to_string());
+
+    let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L>::new([Fr::from(1); L]);
+    let cost = CostModel::measure(&circuit).unwrap();
+    println!("HashCircuit, WIDTH = {}, RATE = {}, L = {}", WIDTH, RATE, L);
+    println!("{}", cost);
+
+    let root = SVGBackend::new(&path, (1024, 3096)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let root = root
+        .titled(
+            &format!("HashCircuit layout, K = {}", cost.min_k),
+            ("sans-serif", 40),
+        )
+        .unwrap();
+    CircuitLayout::default()
+        .show_labels(true)
+        .mark_equality_cells(true)
+        .render(cost.min_k, &circuit, &root)
+        .unwrap();
+    root.present().unwrap();
+    println!("Layout written to {}", path);
+
+    println!();
+    println!("   L | rows | min K | proof (B)");
+    print_cost::<1>();
+    print_cost::<4>();
+    print_cost::<16>();
+    print_cost::<64>();
+}
+
+fn print_cost<const L: usize>() {
+    let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L>::new([Fr::from(1); L]);
+    let cost = CostModel::measure(&circuit).unwrap();
+    println!(
+        "{:>4} | {:>4} | {:>5} | {:>9}",
+        L, cost.rows, cost.min_k, cost.proof_size
+    );
+}
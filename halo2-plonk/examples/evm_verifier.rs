@@ -0,0 +1,47 @@
+use halo2curves::bn256::Fr;
+
+use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+
+use plonk_example::backend::{Backend, Kzg};
+use plonk_example::evm::{calldata, prove, solidity_verifier, verify_on_evm};
+use plonk_example::*;
+
+const K: u32 = 8;
+
+const L: usize = 2;
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+// Writes the verifier contract of `HashCircuit` and the calldata of a proof,
+// and checks them in a local EVM:
+//   cargo run --release --example evm_verifier --features evm
+pub fn main() {
+    let message = [Fr::from(475023450948321098), Fr::from(42)];
+    let commitment =
+        poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+            .hash(message);
+    let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L>::new(message);
+
+    let params = Kzg::setup(K);
+    let pk = Kzg::keygen(&params, &circuit).unwrap();
+    let solidity = solidity_verifier(&params, pk.get_vk(), 1).unwrap();
+    std::fs::write("Verifier.sol", &solidity).unwrap();
+    println!("Verifier contract written to Verifier.sol");
+
+    let proof = prove(&params, &pk, circuit, &[commitment]).unwrap();
+    let calldata = calldata(&[commitment], &proof);
+    let hex = calldata
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    std::fs::write("calldata.hex", format!("0x{}", hex)).unwrap();
+    println!(
+        "Calldata ({} bytes) written to calldata.hex",
+        calldata.len()
+    );
+
+    match verify_on_evm(&solidity, calldata) {
+        Some(gas) => println!("Proof accepted by the contract, using {} gas", gas),
+        None => panic!("Proof rejected by the contract"),
+    }
+}
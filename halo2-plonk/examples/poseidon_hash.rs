@@ -8,20 +8,20 @@ use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
 use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof};
 use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
 use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+use halo2_proofs::poly::VerificationStrategy;
 use halo2_proofs::transcript::{
     Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
 };
 
 use rand_core::OsRng;
 
+use plonk_example::cost::CostModel;
 use plonk_example::*;
 
-// 2^k-th root of unity
-const K: u32 = 8;
-
 const L: usize = 2;
 
-// TODO: find what the constants influence
+// the permutation works on WIDTH words, RATE of which absorb the message; the
+// round numbers for the width follow from `plonk_example::params`
 const WIDTH: usize = 3;
 const RATE: usize = 2;
 
@@ -40,8 +40,9 @@ pub fn main() {
         nullifier, commitment,
     );
 
-    let params = ParamsKZG::<Bn256>::setup(K, OsRng);
     let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L>::new(message);
+    let k = CostModel::measure(&circuit).unwrap().min_k;
+    let params = ParamsKZG::<Bn256>::setup(k, OsRng);
     let vk = keygen_vk::<_, _, _>(&params, &circuit).unwrap();
     let pk = keygen_pk::<_, _, _>(&params, vk.clone(), &circuit).unwrap();
     println!("Keys successfully generated");
@@ -65,14 +66,18 @@ pub fn main() {
     // Verifying
     let strategy = AccumulatorStrategy::new(&params);
     let mut transcript = Blake2bRead::init(&proof[..]);
-    verify_proof::<_, VerifierSHPLONK<_>, _, Blake2bRead<_, _, _>, AccumulatorStrategy<_>>(
-        &params,
-        &vk,
-        strategy,
-        &[&[&public_inputs]],
-        &mut transcript,
-    )
-    .unwrap();
+    let strategy =
+        verify_proof::<_, VerifierSHPLONK<_>, _, Blake2bRead<_, _, _>, AccumulatorStrategy<_>>(
+            &params,
+            &vk,
+            strategy,
+            &[&[&public_inputs]],
+            &mut transcript,
+        )
+        .unwrap();
+    // the accumulator defers the pairing check, which could take in further
+    // proofs as `plonk_example::aggregation` does
+    assert!(strategy.finalize());
 
     println!("Generated proof is correctly verified");
 }
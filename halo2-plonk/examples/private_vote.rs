@@ -0,0 +1,90 @@
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+
+use halo2curves::bn256::{Bn256, Fr};
+use halo2curves::ff::Field;
+
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof};
+use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+
+use rand_core::OsRng;
+
+use plonk_example::cost::CostModel;
+use plonk_example::merkle::MerkleTree;
+use plonk_example::semaphore::{
+    identity_commitment, nullifier_hash, NullifierRegistry, PublicInputs, SemaphoreCircuit,
+};
+use plonk_example::*;
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+const DEPTH: usize = 8;
+
+type Spec = MySpec<WIDTH, RATE>;
+type VoteCircuit = SemaphoreCircuit<Spec, WIDTH, RATE, DEPTH>;
+
+pub fn main() {
+    // registration: every voter publishes an identity commitment
+    let voters = (0..10)
+        .map(|_| (Fr::random(OsRng), Fr::random(OsRng)))
+        .collect::<Vec<_>>();
+    let leaves = voters
+        .iter()
+        .map(|(secret, nullifier)| identity_commitment::<Spec, WIDTH, RATE>(*secret, *nullifier))
+        .collect::<Vec<_>>();
+    let tree = MerkleTree::<Spec, WIDTH, RATE>::new(DEPTH, &leaves);
+    let mut registry = NullifierRegistry::new(tree.root());
+
+    let empty = VoteCircuit::new(Fr::ZERO, Fr::ZERO, &tree.path(0), Fr::ZERO, Fr::ZERO);
+    let k = CostModel::measure(&empty).unwrap().min_k;
+    let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+    let vk = keygen_vk::<_, _, _>(&params, &empty).unwrap();
+    let pk = keygen_pk::<_, _, _>(&params, vk.clone(), &empty).unwrap();
+    println!("Keys successfully generated");
+
+    // poll 1, voter 3 votes "yes" twice
+    let poll = Fr::from(1);
+    let (secret, nullifier) = voters[3];
+    for vote in [Fr::ONE, Fr::ONE] {
+        let inputs = PublicInputs {
+            root: tree.root(),
+            nullifier_hash: nullifier_hash::<Spec, WIDTH, RATE>(nullifier, poll),
+            external_nullifier: poll,
+            signal: vote,
+        };
+        let circuit = VoteCircuit::new(secret, nullifier, &tree.path(3), poll, vote);
+
+        let instance = inputs.to_instance();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[&instance]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let strategy = SingleStrategy::new(&params);
+        let mut transcript = Blake2bRead::init(&proof[..]);
+        verify_proof::<_, VerifierSHPLONK<_>, _, Blake2bRead<_, _, _>, SingleStrategy<_>>(
+            &params,
+            &vk,
+            strategy,
+            &[&[&instance]],
+            &mut transcript,
+        )
+        .unwrap();
+        println!("Vote proof verified");
+
+        match registry.register(&inputs) {
+            Ok(()) => println!("Vote counted"),
+            Err(err) => println!("Vote rejected: {}", err),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+
+use halo2curves::bn256::Bn256;
+
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof};
+use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+use halo2_proofs::poly::VerificationStrategy;
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+
+use rand_core::OsRng;
+
+use plonk_example::variable_length::{self, bytes_to_words, VariableLengthHashCircuit};
+use plonk_example::*;
+
+// the circuit needs one Poseidon permutation per RATE words of padded message
+const K: u32 = 9;
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+pub fn main() {
+    let record = br#"{"id":4711,"owner":"alice","balance":1250,"updated":1700000000}"#;
+    let message = bytes_to_words(record);
+    let digest = variable_length::hash::<MySpec<WIDTH, RATE>, WIDTH, RATE>(&message);
+    println!(
+        "record of {} bytes ({} words) hashed to: {:?}",
+        record.len(),
+        message.len(),
+        digest
+    );
+
+    let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+    let circuit = VariableLengthHashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE>::new(&message);
+    let vk = keygen_vk::<_, _, _>(&params, &circuit).unwrap();
+    let pk = keygen_pk::<_, _, _>(&params, vk.clone(), &circuit).unwrap();
+    println!("Keys successfully generated");
+
+    // Proving
+    let public_inputs = vec![digest];
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>, _>(
+        &params,
+        &pk,
+        &[circuit],
+        &[&[&public_inputs.clone()]],
+        OsRng,
+        &mut transcript,
+    )
+    .unwrap();
+    let proof = transcript.finalize();
+
+    println!("Proof of knowledge created");
+
+    // Verifying
+    let strategy = AccumulatorStrategy::new(&params);
+    let mut transcript = Blake2bRead::init(&proof[..]);
+    let strategy =
+        verify_proof::<_, VerifierSHPLONK<_>, _, Blake2bRead<_, _, _>, AccumulatorStrategy<_>>(
+            &params,
+            &vk,
+            strategy,
+            &[&[&public_inputs]],
+            &mut transcript,
+        )
+        .unwrap();
+    assert!(strategy.finalize());
+
+    println!("Generated proof is correctly verified");
+}
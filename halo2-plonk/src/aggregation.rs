@@ -0,0 +1,160 @@
+//! Batch verification of KZG proofs with a single pairing check.
+//!
+//! A KZG verifier ends with a pairing check of the openings of the proof,
+//! which costs more than the rest of the verifier. halo2's
+//! `AccumulatorStrategy` keeps the two sides of that check as multi-scalar
+//! multiplications instead, and folds those of each further proof into them
+//! after scaling the accumulated ones by a random factor. A batch of proofs,
+//! for the same or different circuits, is then checked with one pairing, and
+//! fails if any of its proofs does, but with negligible probability.
+//!
+//! The aggregation is done natively. Proving it in a circuit, to get a single
+//! proof, would need an in-circuit verifier and is not done here.
+use halo2_proofs::{
+    plonk::{verify_proof, Error, VerifyingKey},
+    poly::{
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::AccumulatorStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer},
+};
+
+use halo2curves::bn256::{Bn256, Fr as Fp, G1Affine};
+
+/// Proofs checked but for their final pairing, as made by
+/// [`crate::backend::Kzg`].
+pub struct Accumulator<'params> {
+    params: &'params ParamsKZG<Bn256>,
+    strategy: AccumulatorStrategy<'params, Bn256>,
+    proofs: usize,
+}
+
+impl<'params> Accumulator<'params> {
+    pub fn new(params: &'params ParamsKZG<Bn256>) -> Self {
+        Self {
+            params,
+            strategy: AccumulatorStrategy::new(params),
+            proofs: 0,
+        }
+    }
+
+    /// Runs the verifier on `proof` up to its pairing check, whose sides are
+    /// folded into the accumulator. A malformed proof fails the whole batch.
+    pub fn accumulate(
+        self,
+        vk: &VerifyingKey<G1Affine>,
+        instance: &[Fp],
+        proof: &[u8],
+    ) -> Result<Self, Error> {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        let strategy = verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+            self.params,
+            vk,
+            self.strategy,
+            &[&[instance]],
+            &mut transcript,
+        )?;
+        Ok(Self {
+            params: self.params,
+            strategy,
+            proofs: self.proofs + 1,
+        })
+    }
+
+    /// Number of proofs accumulated.
+    pub fn len(&self) -> usize {
+        self.proofs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs == 0
+    }
+
+    /// The pairing check of all the accumulated proofs.
+    pub fn finalize(self) -> bool {
+        self.strategy.finalize()
+    }
+}
+
+/// Verifies proofs of the circuit of `vk` with their instances, in one
+/// pairing check.
+pub fn verify_batch<'a>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proofs: impl IntoIterator<Item = (&'a [Fp], &'a [u8])>,
+) -> Result<(), Error> {
+    let accumulator = proofs.into_iter().try_fold(
+        Accumulator::new(params),
+        |accumulator, (instance, proof)| accumulator.accumulate(vk, instance, proof),
+    )?;
+    if accumulator.finalize() {
+        Ok(())
+    } else {
+        Err(Error::ConstraintSystemFailure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Backend, Kzg};
+    use crate::merkle::{MerkleCircuit, MerkleTree};
+    use crate::MySpec;
+
+    const K: u32 = 10;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const DEPTH: usize = 4;
+
+    type TestSpec = MySpec<WIDTH, RATE>;
+    type TestCircuit = MerkleCircuit<TestSpec, WIDTH, RATE, DEPTH>;
+
+    #[test]
+    fn test_batch_of_membership_proofs() {
+        let leaves = (0..5u64).map(|i| Fp::from(100 + i)).collect::<Vec<_>>();
+        let tree = MerkleTree::<TestSpec, WIDTH, RATE>::new(DEPTH, &leaves);
+        let root = [tree.root()];
+
+        let params = Kzg::setup(K);
+        let pk = Kzg::keygen(&params, &TestCircuit::new(tree.leaf(0), &tree.path(0))).unwrap();
+        let vk = pk.get_vk();
+        let proofs = (0..leaves.len())
+            .map(|i| {
+                let circuit = TestCircuit::new(tree.leaf(i), &tree.path(i));
+                Kzg::prove(&params, &pk, circuit, &root).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let batch = || proofs.iter().map(|proof| (&root[..], &proof[..]));
+        assert!(verify_batch(&params, vk, batch()).is_ok());
+
+        let accumulator = batch()
+            .try_fold(
+                Accumulator::new(&params),
+                |accumulator, (instance, proof)| accumulator.accumulate(vk, instance, proof),
+            )
+            .unwrap();
+        assert_eq!(accumulator.len(), leaves.len());
+        assert!(accumulator.finalize());
+
+        // a single proof against another root spoils the batch
+        let other_root = [root[0] + Fp::from(1)];
+        let spoiled = batch()
+            .take(leaves.len() - 1)
+            .chain([(&other_root[..], &proofs[0][..])]);
+        assert!(verify_batch(&params, vk, spoiled).is_err());
+
+        // as does a tampered proof, whether or not it still parses
+        let mut tampered = proofs[1].clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        let spoiled = batch().chain([(&root[..], &tampered[..])]);
+        assert!(verify_batch(&params, vk, spoiled).is_err());
+
+        assert!(Accumulator::new(&params).is_empty());
+    }
+}
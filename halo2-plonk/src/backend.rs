@@ -0,0 +1,244 @@
+//! Proving backends: KZG with SHPLONK on Bn256, which needs a trusted setup,
+//! and the transparent inner product argument on the Pasta curves.
+//!
+//! A circuit generic over its field, like [`crate::HashCircuit`], is proven
+//! with either by choosing the backend as a type parameter, and with any of
+//! the transcripts of [`crate::transcript`] the backend supports.
+use halo2_proofs::{
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+    },
+    poly::{
+        commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy as IpaSingleStrategy,
+        },
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy as KzgSingleStrategy,
+        },
+    },
+    transcript::{TranscriptReadBuffer, TranscriptWriterBuffer},
+};
+
+use halo2curves::bn256::{self, Bn256};
+use halo2curves::ff::FromUniformBytes;
+use halo2curves::pasta::{self, EqAffine};
+use halo2curves::CurveAffine;
+
+use rand_core::OsRng;
+
+use crate::transcript::{Blake2b, TranscriptHash};
+
+pub trait Backend {
+    /// Field of the circuit
+    type Scalar: FromUniformBytes<64> + Ord;
+    /// Curve of the commitments
+    type Curve: CurveAffine<ScalarExt = Self::Scalar>;
+    type Params;
+
+    const NAME: &'static str;
+
+    /// Parameters for circuits of `2^k` rows; a fresh trusted setup for KZG.
+    fn setup(k: u32) -> Self::Params;
+
+    fn keygen<C: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        circuit: &C,
+    ) -> Result<ProvingKey<Self::Curve>, Error>;
+
+    /// Proves `circuit` with a single instance column holding `instance`,
+    /// with the transcript `T`.
+    fn prove_with<T: TranscriptHash<Self::Curve>, C: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: C,
+        instance: &[Self::Scalar],
+    ) -> Result<Vec<u8>, Error>;
+
+    fn verify_with<T: TranscriptHash<Self::Curve>>(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        instance: &[Self::Scalar],
+        proof: &[u8],
+    ) -> Result<(), Error>;
+
+    /// [`Backend::prove_with`] with the [`Blake2b`] transcript, halo2's default.
+    fn prove<C: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: C,
+        instance: &[Self::Scalar],
+    ) -> Result<Vec<u8>, Error> {
+        Self::prove_with::<Blake2b, C>(params, pk, circuit, instance)
+    }
+
+    fn verify(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        instance: &[Self::Scalar],
+        proof: &[u8],
+    ) -> Result<(), Error> {
+        Self::verify_with::<Blake2b>(params, vk, instance, proof)
+    }
+}
+
+/// KZG commitments over Bn256, opened with SHPLONK.
+#[derive(Clone, Copy, Debug)]
+pub struct Kzg;
+
+impl Backend for Kzg {
+    type Scalar = bn256::Fr;
+    type Curve = bn256::G1Affine;
+    type Params = ParamsKZG<Bn256>;
+
+    const NAME: &'static str = "KZG (Bn256)";
+
+    fn setup(k: u32) -> Self::Params {
+        ParamsKZG::setup(k, OsRng)
+    }
+
+    fn keygen<C: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        circuit: &C,
+    ) -> Result<ProvingKey<Self::Curve>, Error> {
+        let vk = keygen_vk(params, circuit)?;
+        keygen_pk(params, vk, circuit)
+    }
+
+    fn prove_with<T: TranscriptHash<Self::Curve>, C: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: C,
+        instance: &[Self::Scalar],
+    ) -> Result<Vec<u8>, Error> {
+        let mut transcript = T::Writer::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[&[instance]],
+            OsRng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    fn verify_with<T: TranscriptHash<Self::Curve>>(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        instance: &[Self::Scalar],
+        proof: &[u8],
+    ) -> Result<(), Error> {
+        let mut transcript = <T::Reader<'_>>::init(proof);
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+            params,
+            vk,
+            KzgSingleStrategy::new(params),
+            &[&[instance]],
+            &mut transcript,
+        )
+    }
+}
+
+/// The inner product argument over the Pasta cycle: circuits over the Pallas
+/// base field, commitments on Vesta. No trusted setup is needed.
+#[derive(Clone, Copy, Debug)]
+pub struct Ipa;
+
+impl Backend for Ipa {
+    type Scalar = pasta::Fp;
+    type Curve = EqAffine;
+    type Params = ParamsIPA<EqAffine>;
+
+    const NAME: &'static str = "IPA (Pasta)";
+
+    fn setup(k: u32) -> Self::Params {
+        ParamsIPA::new(k)
+    }
+
+    fn keygen<C: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        circuit: &C,
+    ) -> Result<ProvingKey<Self::Curve>, Error> {
+        let vk = keygen_vk(params, circuit)?;
+        keygen_pk(params, vk, circuit)
+    }
+
+    fn prove_with<T: TranscriptHash<Self::Curve>, C: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: C,
+        instance: &[Self::Scalar],
+    ) -> Result<Vec<u8>, Error> {
+        let mut transcript = T::Writer::init(vec![]);
+        create_proof::<IPACommitmentScheme<EqAffine>, ProverIPA<_>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[&[instance]],
+            OsRng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    fn verify_with<T: TranscriptHash<Self::Curve>>(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        instance: &[Self::Scalar],
+        proof: &[u8],
+    ) -> Result<(), Error> {
+        let mut transcript = <T::Reader<'_>>::init(proof);
+        verify_proof::<IPACommitmentScheme<EqAffine>, VerifierIPA<_>, _, _, _>(
+            params,
+            vk,
+            IpaSingleStrategy::new(params),
+            &[&[instance]],
+            &mut transcript,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HashCircuit, MySpec};
+    use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength, Spec};
+
+    const K: u32 = 8;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const L: usize = 2;
+
+    fn prove_and_verify<B: Backend>()
+    where
+        MySpec<WIDTH, RATE>: Spec<B::Scalar, WIDTH, RATE>,
+    {
+        let message = [B::Scalar::from(475023450948321098), B::Scalar::from(42)];
+        let commitment =
+            poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+                .hash(message);
+        let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L, B::Scalar>::new(message);
+
+        let params = B::setup(K);
+        let pk = B::keygen(&params, &circuit).unwrap();
+        let proof = B::prove(&params, &pk, circuit, &[commitment]).unwrap();
+        let vk = pk.get_vk();
+        assert!(B::verify(&params, vk, &[commitment], &proof).is_ok());
+        assert!(B::verify(&params, vk, &[commitment + B::Scalar::from(1)], &proof).is_err());
+    }
+
+    #[test]
+    fn test_kzg() {
+        prove_and_verify::<Kzg>();
+    }
+
+    #[test]
+    fn test_ipa() {
+        prove_and_verify::<Ipa>();
+    }
+}
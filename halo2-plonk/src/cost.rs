@@ -0,0 +1,309 @@
+//! Cost model of a circuit: its columns and degree, the rows its layout takes
+//! and from these the smallest `K` it fits in and the size of its proofs.
+//!
+//! The layout is measured by running the circuit's floor planner without
+//! witnesses, so it costs as much as a key generation without the
+//! commitments. The circuit's selectors are compressed into fixed columns as
+//! key generation does, so the counts are those of the proving key.
+use halo2_proofs::{
+    circuit::{FloorPlanner, Value},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, Instance, Selector,
+    },
+};
+
+use halo2curves::ff::Field;
+
+/// Bytes of a compressed point and of a scalar in a Bn256 or Pasta proof.
+const ELEMENT_SIZE: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CostModel {
+    pub advice_columns: usize,
+    /// Fixed columns, including those the selectors are compressed into
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub gates: usize,
+    pub lookups: usize,
+    pub max_degree: usize,
+    /// Rows taken by the regions, constants and public inputs
+    pub rows: usize,
+    /// Rows at the end of the table reserved for blinding
+    pub blinding_rows: usize,
+    /// Smallest `K` for which the circuit fits in the `2^K` rows of the table
+    pub min_k: u32,
+    /// Bytes of a KZG proof opened with SHPLONK
+    pub proof_size: usize,
+}
+
+impl CostModel {
+    pub fn measure<F: Field, C: Circuit<F>>(circuit: &C) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = C::configure(&mut cs);
+        let mut layout = Layout::default();
+        C::FloorPlanner::synthesize(&mut layout, circuit, config, cs.constants().clone())?;
+
+        let selectors = (0..cs.num_selectors())
+            .map(|selector| {
+                let mut enabled = vec![false; layout.rows];
+                for (_, row) in layout.selectors.iter().filter(|(s, _)| *s == selector) {
+                    enabled[*row] = true;
+                }
+                enabled
+            })
+            .collect();
+        let (cs, _) = cs.compress_selectors(selectors);
+
+        let blinding_rows = cs.blinding_factors() + 1;
+        let min_k = (layout.rows + blinding_rows)
+            .max(cs.minimum_rows())
+            .next_power_of_two()
+            .trailing_zeros();
+
+        Ok(Self {
+            advice_columns: cs.num_advice_columns(),
+            fixed_columns: cs.num_fixed_columns(),
+            instance_columns: cs.num_instance_columns(),
+            gates: cs.gates().len(),
+            lookups: cs.lookups().len(),
+            max_degree: cs.degree(),
+            rows: layout.rows,
+            blinding_rows,
+            min_k,
+            proof_size: proof_size(&cs),
+        })
+    }
+
+    /// Rows the circuit can assign in a table of `2^k` rows.
+    pub fn usable_rows(&self, k: u32) -> usize {
+        (1usize << k).saturating_sub(self.blinding_rows)
+    }
+}
+
+impl std::fmt::Display for CostModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "columns:    {} advice, {} fixed, {} instance",
+            self.advice_columns, self.fixed_columns, self.instance_columns
+        )?;
+        writeln!(
+            f,
+            "gates:      {}, {} lookups, max degree {}",
+            self.gates, self.lookups, self.max_degree
+        )?;
+        writeln!(
+            f,
+            "rows:       {} used, {} blinding",
+            self.rows, self.blinding_rows
+        )?;
+        writeln!(
+            f,
+            "minimal K:  {} ({} of {} usable rows used)",
+            self.min_k,
+            self.rows,
+            self.usable_rows(self.min_k)
+        )?;
+        write!(f, "proof size: {} bytes (KZG, SHPLONK)", self.proof_size)
+    }
+}
+
+/// Size of a proof with the prover's messages in the order `create_proof`
+/// writes them. The instance columns are not committed to with KZG.
+fn proof_size<F: Field>(cs: &ConstraintSystem<F>) -> usize {
+    let permutation_columns = cs.permutation().get_columns().len();
+    let permutation_sets = permutation_columns.div_ceil(cs.degree() - 2);
+    let lookups = cs.lookups().len();
+
+    let points = cs.num_advice_columns()
+        // permuted input and table, and product of each lookup
+        + 3 * lookups
+        + permutation_sets
+        // random polynomial and pieces of the quotient
+        + 1
+        + (cs.degree() - 1)
+        // SHPLONK opening
+        + 2;
+    let scalars = cs.advice_queries().len()
+        + cs.fixed_queries().len()
+        // random polynomial
+        + 1
+        + permutation_columns
+        // each product at x and ωx, and all but the last at the last row
+        + (3 * permutation_sets).saturating_sub(1)
+        + 5 * lookups;
+    (points + scalars) * ELEMENT_SIZE
+}
+
+/// Records the rows used by the layout and where selectors are enabled.
+#[derive(Default)]
+struct Layout {
+    rows: usize,
+    selectors: Vec<(usize, usize)>,
+}
+
+impl Layout {
+    fn use_row(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for Layout {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        self.selectors.push((selector.index(), row));
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    // copies into the instance column are how public inputs take rows
+    fn copy(
+        &mut self,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.use_row(left_row.max(right_row));
+        Ok(())
+    }
+
+    // the default values of lookup tables fill otherwise unused rows
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Backend, Kzg};
+    use crate::merkle::{MerkleCircuit, MerkleTree};
+    use crate::{HashCircuit, MySpec};
+    use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+    use halo2_proofs::dev::MockProver;
+    use halo2curves::bn256::Fr as Fp;
+
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+
+    fn hash_circuit<const L: usize>() -> (HashCircuit<MySpec<WIDTH, RATE>, WIDTH, RATE, L>, Fp) {
+        let message = std::array::from_fn(|i| Fp::from(i as u64));
+        let commitment =
+            poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+                .hash(message);
+        (HashCircuit::new(message), commitment)
+    }
+
+    fn fits<C: Circuit<Fp>>(k: u32, circuit: &C, instance: Vec<Fp>) -> bool {
+        match MockProver::run(k, circuit, vec![instance]) {
+            Err(Error::NotEnoughRowsAvailable { .. }) => false,
+            prover => prover.unwrap().verify().is_ok(),
+        }
+    }
+
+    #[test]
+    fn test_min_k_is_minimal() {
+        let (circuit, commitment) = hash_circuit::<2>();
+        let cost = CostModel::measure(&circuit).unwrap();
+        assert!(fits(cost.min_k, &circuit, vec![commitment]));
+        assert!(!fits(cost.min_k - 1, &circuit, vec![commitment]));
+        assert!(cost.rows <= cost.usable_rows(cost.min_k));
+
+        // four permutations
+        let (circuit, commitment) = hash_circuit::<8>();
+        let longer = CostModel::measure(&circuit).unwrap();
+        assert!(longer.rows > cost.rows);
+        assert!(fits(longer.min_k, &circuit, vec![commitment]));
+        assert!(!fits(longer.min_k - 1, &circuit, vec![commitment]));
+
+        let tree = MerkleTree::<MySpec<WIDTH, RATE>, WIDTH, RATE>::new(4, &[Fp::from(1)]);
+        let circuit =
+            MerkleCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, 4>::new(tree.leaf(0), &tree.path(0));
+        let cost = CostModel::measure(&circuit).unwrap();
+        assert!(fits(cost.min_k, &circuit, vec![tree.root()]));
+        assert!(!fits(cost.min_k - 1, &circuit, vec![tree.root()]));
+    }
+
+    #[test]
+    fn test_proof_size() {
+        let (circuit, commitment) = hash_circuit::<2>();
+        let cost = CostModel::measure(&circuit).unwrap();
+        assert_eq!(cost.advice_columns, WIDTH + 1);
+        assert_eq!(cost.instance_columns, 1);
+
+        let params = Kzg::setup(cost.min_k);
+        let pk = Kzg::keygen(&params, &circuit).unwrap();
+        let proof = Kzg::prove(&params, &pk, circuit, &[commitment]).unwrap();
+        assert_eq!(proof.len(), cost.proof_size);
+    }
+}
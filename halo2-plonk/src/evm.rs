@@ -0,0 +1,158 @@
+//! Verification of KZG proofs on Ethereum.
+//!
+//! The verifier contract is generated from a verifying key by
+//! `halo2_solidity_verifier`, for SHPLONK openings and a single instance
+//! column. It draws the challenges from the [`Keccak`] transcript, laid out
+//! as the contract reads it, so the proofs it accepts are made with [`prove`]
+//! rather than with Blake2b as [`Kzg::prove`] does. The contract takes the
+//! proof and the public inputs as the calldata of its
+//! `verifyProof(bytes,uint256[])` function.
+//!
+//! With the `evm` feature, [`verify_on_evm`] compiles the contract with a
+//! `solc` from the path and runs it in an in-process revm.
+use halo2_proofs::{
+    plonk::{Circuit, Error, ProvingKey, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+};
+
+use halo2curves::bn256::{Bn256, Fr as Fp, G1Affine};
+
+use halo2_solidity_verifier::{encode_calldata, BatchOpenScheme, SolidityGenerator};
+
+use crate::backend::{Backend, Kzg};
+use crate::transcript::Keccak;
+
+/// Source of a contract verifying proofs of the circuit of `vk` with
+/// `num_instances` public inputs.
+pub fn solidity_verifier(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instances: usize,
+) -> Result<String, std::fmt::Error> {
+    SolidityGenerator::new(params, vk, BatchOpenScheme::Bdfg21, num_instances).render()
+}
+
+/// Proves `circuit` with the Keccak-256 transcript, for the verifier contract.
+pub fn prove<C: Circuit<Fp>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instance: &[Fp],
+) -> Result<Vec<u8>, Error> {
+    Kzg::prove_with::<Keccak, C>(params, pk, circuit, instance)
+}
+
+/// Verifies a proof of [`prove`] natively, as the contract would.
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instance: &[Fp],
+    proof: &[u8],
+) -> Result<(), Error> {
+    Kzg::verify_with::<Keccak>(params, vk, instance, proof)
+}
+
+/// Calldata of `verifyProof(proof, instance)` on the verifier contract.
+pub fn calldata(instance: &[Fp], proof: &[u8]) -> Vec<u8> {
+    encode_calldata(None, proof, instance)
+}
+
+/// Deploys the verifier contract `solidity` to a fresh in-process EVM and
+/// calls it with `calldata`. Returns the gas used if it accepts the proof and
+/// `None` if it reverts. Panics if the contract does not compile or deploy.
+#[cfg(feature = "evm")]
+pub fn verify_on_evm(solidity: &str, calldata: Vec<u8>) -> Option<u64> {
+    use halo2_solidity_verifier::{compile_solidity, Evm};
+    use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+    let bytecode = compile_solidity(solidity);
+    assert!(!bytecode.is_empty(), "solc produced no bytecode");
+    let mut evm = Evm::default();
+    let address = evm.create(bytecode);
+
+    // the EVM panics on a reverted call, any other panic is a bug
+    let (gas, output) = match catch_unwind(AssertUnwindSafe(|| evm.call(address, calldata))) {
+        Ok(result) => result,
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| panic.downcast_ref::<&str>().copied());
+            match message {
+                Some(message) if message.starts_with("Transaction reverts") => return None,
+                _ => resume_unwind(panic),
+            }
+        }
+    };
+    let accepted = output.len() == 32 && output[..31].iter().all(|b| *b == 0) && output[31] == 1;
+    accepted.then_some(gas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HashCircuit, MySpec};
+    use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+    use halo2curves::ff::PrimeField;
+
+    const K: u32 = 8;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const L: usize = 2;
+
+    type TestCircuit = HashCircuit<MySpec<WIDTH, RATE>, WIDTH, RATE, L>;
+
+    fn setup() -> (ParamsKZG<Bn256>, ProvingKey<G1Affine>, Vec<u8>, Fp) {
+        let message = [Fp::from(475023450948321098), Fp::from(42)];
+        let commitment =
+            poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+                .hash(message);
+        let params = Kzg::setup(K);
+        let pk = Kzg::keygen(&params, &TestCircuit::new(message)).unwrap();
+        let proof = prove(&params, &pk, TestCircuit::new(message), &[commitment]).unwrap();
+        (params, pk, proof, commitment)
+    }
+
+    #[test]
+    fn test_keccak_proof() {
+        let (params, pk, proof, commitment) = setup();
+        let vk = pk.get_vk();
+        assert!(verify(&params, vk, &[commitment], &proof).is_ok());
+        assert!(verify(&params, vk, &[commitment + Fp::from(1)], &proof).is_err());
+        // the transcript is part of the proof system
+        assert!(Kzg::verify(&params, vk, &[commitment], &proof).is_err());
+
+        // the instance goes last, as a big-endian uint256
+        let calldata = calldata(&[commitment], &proof);
+        let mut instance = commitment.to_repr();
+        instance.reverse();
+        assert!(calldata.ends_with(&instance));
+        assert!(calldata.windows(proof.len()).any(|window| window == proof));
+
+        let solidity = solidity_verifier(&params, vk, 1).unwrap();
+        assert!(solidity.contains("function verifyProof"));
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn test_verify_on_evm() {
+        let (params, pk, proof, commitment) = setup();
+        let solidity = solidity_verifier(&params, pk.get_vk(), 1).unwrap();
+
+        assert!(verify_on_evm(&solidity, calldata(&[commitment], &proof)).is_some());
+
+        let wrong = calldata(&[commitment + Fp::from(1)], &proof);
+        assert_eq!(verify_on_evm(&solidity, wrong), None);
+        let blake2b = Kzg::prove(
+            &params,
+            &pk,
+            TestCircuit::new([Fp::from(475023450948321098), Fp::from(42)]),
+            &[commitment],
+        )
+        .unwrap();
+        assert_eq!(
+            verify_on_evm(&solidity, calldata(&[commitment], &blake2b)),
+            None
+        );
+    }
+}
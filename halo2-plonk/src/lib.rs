@@ -1,10 +1,10 @@
 use halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner, Value},
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
     plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
 };
 
 use halo2curves::bn256::Fr as Fp;
-use halo2curves::ff::Field;
+use halo2curves::ff::{Field, FromUniformBytes, PrimeField};
 
 use halo2_poseidon::poseidon::{
     primitives::{generate_constants, ConstantLength, Mds, Spec},
@@ -13,50 +13,39 @@ use halo2_poseidon::poseidon::{
 use std::convert::TryInto;
 use std::marker::PhantomData;
 
+pub mod aggregation;
+pub mod backend;
+pub mod cost;
+pub mod evm;
+pub mod merkle;
+pub mod params;
+pub mod range;
+pub mod semaphore;
+pub mod serialization;
+pub mod transcript;
+pub mod variable_length;
+
+/// Proves knowledge of a message of `L` words hashing to the public instance,
+/// over the scalar field `F` of the proving backend.
 #[derive(Clone, Copy, Default)]
-pub struct HashCircuit<S, const WIDTH: usize, const RATE: usize, const L: usize>
+pub struct HashCircuit<S, const WIDTH: usize, const RATE: usize, const L: usize, F = Fp>
 where
-    S: Spec<Fp, WIDTH, RATE> + Clone + Copy,
+    F: Field,
+    S: Spec<F, WIDTH, RATE> + Clone + Copy,
 {
-    message: Value<[Fp; L]>,
+    message: Value<[F; L]>,
     _spec: PhantomData<S>,
 }
 
 #[derive(Debug, Clone)]
-pub struct MyConfig<const WIDTH: usize, const RATE: usize, const L: usize> {
-    input: [Column<Advice>; L],
+pub struct MyConfig<const WIDTH: usize, const RATE: usize, F: Field = Fp> {
+    input: [Column<Advice>; RATE],
     expected: Column<Instance>,
-    poseidon_config: Pow5Config<Fp, WIDTH, RATE>,
+    poseidon_config: Pow5Config<F, WIDTH, RATE>,
 }
 
-impl<S, const WIDTH: usize, const RATE: usize, const L: usize> HashCircuit<S, WIDTH, RATE, L>
-where
-    S: Spec<Fp, WIDTH, RATE> + Clone + Copy,
-{
-    pub fn new(message: [Fp; L]) -> Self {
-        Self {
-            message: Value::known(message),
-            _spec: PhantomData,
-        }
-    }
-}
-
-impl<S, const WIDTH: usize, const RATE: usize, const L: usize> Circuit<Fp>
-    for HashCircuit<S, WIDTH, RATE, L>
-where
-    S: Spec<Fp, WIDTH, RATE> + Copy + Clone,
-{
-    type Config = MyConfig<WIDTH, RATE, L>;
-    type FloorPlanner = SimpleFloorPlanner;
-
-    fn without_witnesses(&self) -> Self {
-        Self {
-            message: Value::unknown(),
-            _spec: PhantomData,
-        }
-    }
-
-    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+impl<const WIDTH: usize, const RATE: usize, F: Field> MyConfig<WIDTH, RATE, F> {
+    pub fn configure<S: Spec<F, WIDTH, RATE>>(meta: &mut ConstraintSystem<F>) -> Self {
         let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
         let expected = meta.instance_column();
         meta.enable_equality(expected);
@@ -67,7 +56,7 @@ where
 
         meta.enable_constant(rc_b[0]);
 
-        Self::Config {
+        Self {
             input: state[..RATE].try_into().unwrap(),
             expected,
             poseidon_config: Pow5Chip::configure::<S>(
@@ -80,45 +69,98 @@ where
         }
     }
 
+    /// Assigns the message words `RATE` per row, so that messages of any
+    /// length fit in the input columns.
+    fn load_message(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        layouter.assign_region(
+            || "load message",
+            |mut region| {
+                message
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        region.assign_advice(
+                            || format!("load message_{}", i),
+                            self.input[i % RATE],
+                            i / RATE,
+                            || *value,
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+impl<S, const WIDTH: usize, const RATE: usize, const L: usize, F> HashCircuit<S, WIDTH, RATE, L, F>
+where
+    F: Field,
+    S: Spec<F, WIDTH, RATE> + Clone + Copy,
+{
+    pub fn new(message: [F; L]) -> Self {
+        Self {
+            message: Value::known(message),
+            _spec: PhantomData,
+        }
+    }
+}
+
+impl<S, const WIDTH: usize, const RATE: usize, const L: usize, F> Circuit<F>
+    for HashCircuit<S, WIDTH, RATE, L, F>
+where
+    F: PrimeField,
+    S: Spec<F, WIDTH, RATE> + Copy + Clone,
+{
+    type Config = MyConfig<WIDTH, RATE, F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            message: Value::unknown(),
+            _spec: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MyConfig::configure::<S>(meta)
+    }
+
     fn synthesize(
         &self,
         config: Self::Config,
-        mut layouter: impl Layouter<Fp>,
+        mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let chip = Pow5Chip::construct(config.poseidon_config.clone());
 
-        let message = layouter.assign_region(
-            || "load message",
-            |mut region| {
-                let message_word = |i: usize| {
-                    let value = self.message.map(|message_vals| message_vals[i]);
-                    region.assign_advice(
-                        || format!("load message_{}", i),
-                        config.input[i],
-                        0,
-                        || value,
-                    )
-                };
-
-                let message: Result<Vec<_>, Error> = (0..L).map(message_word).collect();
-                Ok(message?.try_into().unwrap())
-            },
-        )?;
+        let message = (0..L)
+            .map(|i| self.message.map(|message_vals| message_vals[i]))
+            .collect::<Vec<_>>();
+        let message = config.load_message(layouter.namespace(|| "load message"), &message)?;
 
         let hasher = Hash::<_, _, S, ConstantLength<L>, WIDTH, RATE>::init(
             chip,
             layouter.namespace(|| "init"),
         )?;
-        let output = hasher.hash(layouter.namespace(|| "hash"), message)?;
+        let output = hasher.hash(layouter.namespace(|| "hash"), message.try_into().unwrap())?;
 
         layouter.constrain_instance(output.cell(), config.expected, 0)
     }
 }
 
+/// Poseidon with `x^5`, 8 full and 56 partial rounds. Over Bn256 this is
+/// [`params::Pow5Spec`] for widths 2 to 5; wider states need 57 partial rounds.
+/// Over the Pasta fields with a width of 3 it is `P128Pow5T3`.
 #[derive(Debug, Clone, Copy)]
 pub struct MySpec<const WIDTH: usize, const RATE: usize>;
 
-impl<const WIDTH: usize, const RATE: usize> Spec<Fp, WIDTH, RATE> for MySpec<WIDTH, RATE> {
+impl<F, const WIDTH: usize, const RATE: usize> Spec<F, WIDTH, RATE> for MySpec<WIDTH, RATE>
+where
+    F: FromUniformBytes<64> + Ord,
+{
     fn full_rounds() -> usize {
         8
     }
@@ -127,7 +169,7 @@ impl<const WIDTH: usize, const RATE: usize> Spec<Fp, WIDTH, RATE> for MySpec<WID
         56
     }
 
-    fn sbox(val: Fp) -> Fp {
+    fn sbox(val: F) -> F {
         val.pow_vartime(&[5])
     }
 
@@ -135,7 +177,207 @@ impl<const WIDTH: usize, const RATE: usize> Spec<Fp, WIDTH, RATE> for MySpec<WID
         0
     }
 
-    fn constants() -> (Vec<[Fp; WIDTH]>, Mds<Fp, WIDTH>, Mds<Fp, WIDTH>) {
+    fn constants() -> (Vec<[F; WIDTH]>, Mds<F, WIDTH>, Mds<F, WIDTH>) {
         generate_constants::<_, Self, WIDTH, RATE>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::CostModel;
+    use halo2_poseidon::poseidon::primitives as poseidon;
+    use halo2_proofs::dev::{FailureLocation, MockProver, VerifyFailure};
+    use halo2_proofs::plonk::Any;
+
+    fn message<const L: usize>() -> [Fp; L] {
+        std::array::from_fn(|i| Fp::from(1000 + i as u64))
+    }
+
+    fn commitment<const WIDTH: usize, const RATE: usize, const L: usize>(message: [Fp; L]) -> Fp {
+        poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+            .hash(message)
+    }
+
+    fn prover<const WIDTH: usize, const RATE: usize, const L: usize>(
+        k: u32,
+        message: [Fp; L],
+        commitment: Fp,
+    ) -> Result<MockProver<Fp>, Error> {
+        let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L>::new(message);
+        MockProver::run(k, &circuit, vec![vec![commitment]])
+    }
+
+    fn accepts<const WIDTH: usize, const RATE: usize, const L: usize>() {
+        let message = message::<L>();
+        let prover = prover::<WIDTH, RATE, L>(10, message, commitment::<WIDTH, RATE, L>(message));
+        assert_eq!(
+            prover.unwrap().verify(),
+            Ok(()),
+            "WIDTH = {}, RATE = {}, L = {}",
+            WIDTH,
+            RATE,
+            L
+        );
+    }
+
+    #[test]
+    fn test_accepts_valid_commitments() {
+        accepts::<3, 2, 1>();
+        accepts::<3, 2, 2>();
+        accepts::<3, 2, 5>();
+        accepts::<4, 3, 7>();
+        accepts::<5, 4, 4>();
+    }
+
+    #[test]
+    fn test_rejects_wrong_commitment() {
+        let message = message::<2>();
+        let wrong = commitment::<3, 2, 2>([message[1], message[0]]);
+        let failures = prover::<3, 2, 2>(10, message, wrong)
+            .unwrap()
+            .verify()
+            .unwrap_err();
+
+        // the public instance cell is not a copy of the hash output
+        assert!(failures.contains(&VerifyFailure::Permutation {
+            column: (Any::Instance, 0).into(),
+            location: FailureLocation::OutsideRegion { row: 0 },
+        }));
+    }
+
+    /// [`HashCircuit`] with the first message word overwritten in the witness
+    /// after it has been copied into the sponge, which still hashes the
+    /// original message.
+    #[derive(Clone, Copy)]
+    struct TamperedCircuit {
+        message: [Fp; 2],
+        tampered: Fp,
+    }
+
+    impl Circuit<Fp> for TamperedCircuit {
+        type Config = MyConfig<3, 2>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            *self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            MyConfig::configure::<MySpec<3, 2>>(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let message = layouter.assign_region(
+                || "load message",
+                |mut region| {
+                    let message = self
+                        .message
+                        .iter()
+                        .enumerate()
+                        .map(|(i, word)| {
+                            region.assign_advice(
+                                || format!("load message_{}", i),
+                                config.input[i],
+                                0,
+                                || Value::known(*word),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    region.assign_advice(
+                        || "tamper message_0",
+                        config.input[0],
+                        0,
+                        || Value::known(self.tampered),
+                    )?;
+                    Ok(message)
+                },
+            )?;
+
+            let chip = Pow5Chip::construct(config.poseidon_config.clone());
+            let hasher = Hash::<_, _, MySpec<3, 2>, ConstantLength<2>, 3, 2>::init(
+                chip,
+                layouter.namespace(|| "init"),
+            )?;
+            let output = hasher.hash(layouter.namespace(|| "hash"), message.try_into().unwrap())?;
+            layouter.constrain_instance(output.cell(), config.expected, 0)
+        }
+    }
+
+    #[test]
+    fn test_rejects_tampered_advice_cell() {
+        let message = message::<2>();
+        let circuit = TamperedCircuit {
+            message,
+            tampered: Fp::from(7),
+        };
+        let prover =
+            MockProver::run(10, &circuit, vec![vec![commitment::<3, 2, 2>(message)]]).unwrap();
+        let failures = prover.verify().unwrap_err();
+
+        assert!(failures.contains(&VerifyFailure::Permutation {
+            column: (Any::advice(), 0).into(),
+            location: FailureLocation::InRegion {
+                region: (0, "load message").into(),
+                offset: 0,
+            },
+        }));
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+
+        // the untampered witness passes
+        let circuit = TamperedCircuit {
+            message,
+            tampered: message[0],
+        };
+        let prover =
+            MockProver::run(10, &circuit, vec![vec![commitment::<3, 2, 2>(message)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    /// The cost of the configuration, and its fixed columns and selectors
+    /// before the selectors are compressed.
+    fn cost<const WIDTH: usize, const RATE: usize, const L: usize>() -> (CostModel, usize, usize) {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L>::configure(&mut cs);
+
+        let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L>::new(message::<L>());
+        let cost = CostModel::measure(&circuit).unwrap();
+        println!("WIDTH = {}, RATE = {}, L = {}\n{}\n", WIDTH, RATE, L, cost);
+        (cost, cs.num_fixed_columns(), cs.num_selectors())
+    }
+
+    /// Run with `--nocapture` for the report of each configuration.
+    #[test]
+    fn test_cost() {
+        let costs = [
+            (3, cost::<3, 2, 2>()),
+            (3, cost::<3, 2, 8>()),
+            (4, cost::<4, 3, 6>()),
+            (5, cost::<5, 4, 4>()),
+            (5, cost::<5, 4, 16>()),
+        ];
+        for (width, (cost, fixed_columns, selectors)) in &costs {
+            // the state and the partial S-box
+            assert_eq!(cost.advice_columns, width + 1);
+            // two sets of round constants
+            assert_eq!(*fixed_columns, 2 * width);
+            // full rounds, partial rounds and pad-and-add
+            assert_eq!(*selectors, 3);
+            assert!((fixed_columns + 1..=fixed_columns + selectors).contains(&cost.fixed_columns));
+            assert_eq!(cost.instance_columns, 1);
+            assert_eq!(cost.gates, 3);
+            assert_eq!(cost.lookups, 0);
+        }
+        // each permutation takes rows
+        let [(_, (short, ..)), (_, (long, ..)), _, (_, (wide_short, ..)), (_, (wide_long, ..))] =
+            &costs;
+        assert!(long.rows > short.rows && long.min_k >= short.min_k);
+        assert!(wide_long.rows > wide_short.rows && wide_long.min_k >= wide_short.min_k);
+    }
+}
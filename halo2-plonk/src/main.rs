@@ -0,0 +1,174 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit};
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+
+use halo2curves::bn256::{Bn256, Fr};
+use halo2curves::ff::{Field, PrimeField};
+
+use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+
+use rand_core::OsRng;
+
+use plonk_example::serialization::{
+    read_params, read_pk, read_vk, write_params, write_pk, write_vk, ProofFile,
+};
+use plonk_example::*;
+
+const L: usize = 2;
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+type CommitmentCircuit = HashCircuit<MySpec<WIDTH, RATE>, WIDTH, RATE, L>;
+
+/// Proves knowledge of the secret and nullifier behind a Poseidon commitment.
+/// Field elements are given in decimal or as 0x-prefixed big-endian hex.
+#[derive(Parser)]
+#[command(name = "halo2-plonk")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates the KZG parameters and the keys of the commitment circuit
+    Setup {
+        /// log2 of the number of rows
+        #[arg(short, long, default_value_t = 8)]
+        k: u32,
+        #[arg(long, default_value = "keys")]
+        keys: PathBuf,
+    },
+    /// Prints the commitment to a secret and a nullifier, random if omitted
+    Commit {
+        #[arg(long)]
+        secret: String,
+        #[arg(long)]
+        nullifier: Option<String>,
+    },
+    /// Writes a proof of knowledge of the secret and nullifier
+    Prove {
+        #[arg(long)]
+        secret: String,
+        #[arg(long)]
+        nullifier: String,
+        #[arg(long, default_value = "keys")]
+        keys: PathBuf,
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+    },
+    /// Checks a proof against a public commitment
+    Verify {
+        #[arg(long)]
+        commitment: String,
+        #[arg(long, default_value = "keys")]
+        keys: PathBuf,
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+    },
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    match Cli::parse().command {
+        Command::Setup { k, keys } => {
+            let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+            let circuit = CommitmentCircuit::new([Fr::ZERO; L]).without_witnesses();
+            let vk = keygen_vk(&params, &circuit)?;
+            let pk = keygen_pk(&params, vk.clone(), &circuit)?;
+
+            std::fs::create_dir_all(&keys)?;
+            write_params(keys.join("params.bin"), &params)?;
+            write_vk(keys.join("vk.bin"), &vk)?;
+            write_pk(keys.join("pk.bin"), &pk)?;
+            println!("Parameters and keys written to {}", keys.display());
+        }
+        Command::Commit { secret, nullifier } => {
+            let secret = parse_field(&secret)?;
+            let nullifier = match nullifier {
+                Some(nullifier) => parse_field(&nullifier)?,
+                None => Fr::random(OsRng),
+            };
+            println!("nullifier:  {:?}", nullifier);
+            println!("commitment: {:?}", commit(secret, nullifier));
+        }
+        Command::Prove {
+            secret,
+            nullifier,
+            keys,
+            proof,
+        } => {
+            let message = [parse_field(&secret)?, parse_field(&nullifier)?];
+            let params = read_params(keys.join("params.bin"))?;
+            let pk = read_pk::<CommitmentCircuit>(keys.join("pk.bin"))?;
+
+            let public_inputs = vec![commit(message[0], message[1])];
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>, _>(
+                &params,
+                &pk,
+                &[CommitmentCircuit::new(message)],
+                &[&[&public_inputs]],
+                OsRng,
+                &mut transcript,
+            )?;
+            ProofFile::new(public_inputs, transcript.finalize()).save(&proof)?;
+            println!("Proof written to {}", proof.display());
+        }
+        Command::Verify {
+            commitment,
+            keys,
+            proof,
+        } => {
+            let commitment = parse_field(&commitment)?;
+            let params = read_params(keys.join("params.bin"))?;
+            let vk = read_vk::<CommitmentCircuit>(keys.join("vk.bin"))?;
+            let proof = ProofFile::load(&proof)?;
+            if proof.public_inputs != [commitment] {
+                return Err("the proof is for another commitment".into());
+            }
+
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof.proof[..]);
+            verify_proof::<_, VerifierSHPLONK<_>, _, Blake2bRead<_, _, _>, SingleStrategy<_>>(
+                &params,
+                &vk,
+                SingleStrategy::new(&params),
+                &[&[&proof.public_inputs]],
+                &mut transcript,
+            )?;
+            println!("Proof is valid");
+        }
+    }
+    Ok(())
+}
+
+fn commit(secret: Fr, nullifier: Fr) -> Fr {
+    poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+        .hash([secret, nullifier])
+}
+
+/// Parses a decimal number, or 0x-prefixed big-endian hex as printed by the
+/// `Debug` implementation of `Fr`.
+fn parse_field(value: &str) -> Result<Fr, Box<dyn Error>> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) if hex.len() <= 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            let hex = format!("{:0>64}", hex);
+            let mut repr = [0u8; 32];
+            for (i, byte) in repr.iter_mut().rev().enumerate() {
+                *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)?;
+            }
+            Option::from(Fr::from_repr(repr))
+        }
+        Some(_) => None,
+        None => Fr::from_str_vartime(value),
+    };
+    parsed.ok_or_else(|| format!("{} is not a field element", value).into())
+}
@@ -0,0 +1,380 @@
+//! Merkle inclusion proofs over Poseidon.
+//!
+//! Every node is the `ConstantLength<2>` hash of its two children. Going up a
+//! path, a path-index bit per level tells whether the current node is the right
+//! child, in which case a conditional swap gate exchanges it with its sibling
+//! before hashing.
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use halo2curves::bn256::Fr as Fp;
+use halo2curves::ff::Field;
+
+use halo2_poseidon::poseidon::{
+    primitives::{self as poseidon, ConstantLength, Spec},
+    Hash, Pow5Chip,
+};
+use std::marker::PhantomData;
+
+use crate::MyConfig;
+
+/// Hashes two sibling nodes into their parent.
+pub fn hash_pair<S, const WIDTH: usize, const RATE: usize>(left: Fp, right: Fp) -> Fp
+where
+    S: Spec<Fp, WIDTH, RATE>,
+{
+    poseidon::Hash::<_, S, ConstantLength<2>, WIDTH, RATE>::init().hash([left, right])
+}
+
+/// Siblings from the leaf up to the root, and whether the node on the path is
+/// the right child at each level.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub siblings: Vec<Fp>,
+    pub indices: Vec<bool>,
+}
+
+impl MerklePath {
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    /// Hashes `leaf` up the path.
+    pub fn root<S, const WIDTH: usize, const RATE: usize>(&self, leaf: Fp) -> Fp
+    where
+        S: Spec<Fp, WIDTH, RATE>,
+    {
+        self.siblings
+            .iter()
+            .zip(&self.indices)
+            .fold(leaf, |node, (sibling, is_right)| {
+                if *is_right {
+                    hash_pair::<S, WIDTH, RATE>(*sibling, node)
+                } else {
+                    hash_pair::<S, WIDTH, RATE>(node, *sibling)
+                }
+            })
+    }
+}
+
+/// Complete binary tree of `2^depth` leaves, missing leaves being zero.
+#[derive(Clone, Debug)]
+pub struct MerkleTree<S, const WIDTH: usize, const RATE: usize>
+where
+    S: Spec<Fp, WIDTH, RATE>,
+{
+    levels: Vec<Vec<Fp>>,
+    _spec: PhantomData<S>,
+}
+
+impl<S, const WIDTH: usize, const RATE: usize> MerkleTree<S, WIDTH, RATE>
+where
+    S: Spec<Fp, WIDTH, RATE>,
+{
+    pub fn new(depth: usize, leaves: &[Fp]) -> Self {
+        assert!(leaves.len() <= 1 << depth, "Too many leaves for the depth");
+
+        let mut level = leaves.to_vec();
+        level.resize(1 << depth, Fp::ZERO);
+        let mut levels = vec![level];
+        for _ in 0..depth {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| hash_pair::<S, WIDTH, RATE>(pair[0], pair[1]))
+                .collect();
+            levels.push(level);
+        }
+        Self {
+            levels,
+            _spec: PhantomData,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn root(&self) -> Fp {
+        self.levels[self.depth()][0]
+    }
+
+    pub fn leaf(&self, index: usize) -> Fp {
+        self.levels[0][index]
+    }
+
+    /// Authentication path of the leaf at `index`.
+    pub fn path(&self, index: usize) -> MerklePath {
+        assert!(index < self.levels[0].len(), "Leaf index out of range");
+
+        let (siblings, indices) = self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| {
+                let position = index >> height;
+                (level[position ^ 1], position & 1 == 1)
+            })
+            .unzip();
+        MerklePath { siblings, indices }
+    }
+}
+
+/// Poseidon columns plus the three advice columns of the swap gate:
+///
+/// | a    | b     | bit |
+/// | left | right |     |
+#[derive(Debug, Clone)]
+pub struct MerkleConfig<const WIDTH: usize, const RATE: usize> {
+    pub(crate) hash: MyConfig<WIDTH, RATE>,
+    swap: [Column<Advice>; 3],
+    s_swap: Selector,
+}
+
+impl<const WIDTH: usize, const RATE: usize> MerkleConfig<WIDTH, RATE> {
+    pub fn configure<S: Spec<Fp, WIDTH, RATE>>(meta: &mut ConstraintSystem<Fp>) -> Self {
+        let hash = MyConfig::configure::<S>(meta);
+        let swap = [(); 3].map(|_| meta.advice_column());
+        for column in swap {
+            meta.enable_equality(column);
+        }
+        let s_swap = meta.selector();
+
+        meta.create_gate("conditional swap", |meta| {
+            let s = meta.query_selector(s_swap);
+            let a = meta.query_advice(swap[0], Rotation::cur());
+            let b = meta.query_advice(swap[1], Rotation::cur());
+            let bit = meta.query_advice(swap[2], Rotation::cur());
+            let left = meta.query_advice(swap[0], Rotation::next());
+            let right = meta.query_advice(swap[1], Rotation::next());
+            let one = Expression::Constant(Fp::ONE);
+
+            vec![
+                s.clone() * bit.clone() * (one - bit.clone()),
+                s.clone() * (left - a.clone() - bit.clone() * (b.clone() - a.clone())),
+                s * (right - b.clone() - bit * (a - b)),
+            ]
+        });
+
+        Self { hash, swap, s_swap }
+    }
+
+    /// Hashes `leaf` up the path given by `siblings` and `indices` and returns
+    /// the assigned root, so that larger circuits can constrain it as they see
+    /// fit.
+    pub(crate) fn assign_root<S: Spec<Fp, WIDTH, RATE>>(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        leaf: AssignedCell<Fp, Fp>,
+        siblings: &[Value<Fp>],
+        indices: &[Value<Fp>],
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let mut node = leaf;
+        for (level, (sibling, bit)) in siblings.iter().zip(indices).enumerate() {
+            let [left, right] = self.swap(
+                layouter.namespace(|| format!("swap_{}", level)),
+                &node,
+                *sibling,
+                *bit,
+            )?;
+
+            node = self.hash_pair::<S>(
+                layouter.namespace(|| format!("hash_{}", level)),
+                left,
+                right,
+            )?;
+        }
+        Ok(node)
+    }
+
+    /// Constrains the `ConstantLength<2>` hash of two assigned words.
+    pub(crate) fn hash_pair<S: Spec<Fp, WIDTH, RATE>>(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        left: AssignedCell<Fp, Fp>,
+        right: AssignedCell<Fp, Fp>,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let chip = Pow5Chip::construct(self.hash.poseidon_config.clone());
+        let hasher = Hash::<_, _, S, ConstantLength<2>, WIDTH, RATE>::init(
+            chip,
+            layouter.namespace(|| "init"),
+        )?;
+        hasher.hash(layouter.namespace(|| "hash"), [left, right])
+    }
+
+    /// Orders `node` and `sibling` as (left, right) children, swapping them
+    /// when `bit` is one.
+    fn swap(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        node: &AssignedCell<Fp, Fp>,
+        sibling: Value<Fp>,
+        bit: Value<Fp>,
+    ) -> Result<[AssignedCell<Fp, Fp>; 2], Error> {
+        layouter.assign_region(
+            || "conditional swap",
+            |mut region| {
+                self.s_swap.enable(&mut region, 0)?;
+
+                let a = node.copy_advice(|| "node", &mut region, self.swap[0], 0)?;
+                let b = region.assign_advice(|| "sibling", self.swap[1], 0, || sibling)?;
+                region.assign_advice(|| "bit", self.swap[2], 0, || bit)?;
+
+                let (a, b) = (a.value().copied(), b.value().copied());
+                let swapped = bit.map(|bit| bit == Fp::ONE);
+                let left = swapped
+                    .zip(a.zip(b))
+                    .map(|(s, (a, b))| if s { b } else { a });
+                let right = swapped
+                    .zip(a.zip(b))
+                    .map(|(s, (a, b))| if s { a } else { b });
+                Ok([
+                    region.assign_advice(|| "left", self.swap[0], 1, || left)?,
+                    region.assign_advice(|| "right", self.swap[1], 1, || right)?,
+                ])
+            },
+        )
+    }
+}
+
+/// Proves that a private leaf belongs to the tree whose root is the public
+/// instance, along a private path of `DEPTH` levels.
+#[derive(Clone, Copy)]
+pub struct MerkleCircuit<S, const WIDTH: usize, const RATE: usize, const DEPTH: usize>
+where
+    S: Spec<Fp, WIDTH, RATE> + Clone + Copy,
+{
+    leaf: Value<Fp>,
+    siblings: [Value<Fp>; DEPTH],
+    indices: [Value<Fp>; DEPTH],
+    _spec: PhantomData<S>,
+}
+
+impl<S, const WIDTH: usize, const RATE: usize, const DEPTH: usize>
+    MerkleCircuit<S, WIDTH, RATE, DEPTH>
+where
+    S: Spec<Fp, WIDTH, RATE> + Clone + Copy,
+{
+    pub fn new(leaf: Fp, path: &MerklePath) -> Self {
+        assert_eq!(path.depth(), DEPTH, "Invalid path depth");
+        Self {
+            leaf: Value::known(leaf),
+            siblings: std::array::from_fn(|i| Value::known(path.siblings[i])),
+            indices: std::array::from_fn(|i| Value::known(Fp::from(path.indices[i] as u64))),
+            _spec: PhantomData,
+        }
+    }
+}
+
+impl<S, const WIDTH: usize, const RATE: usize, const DEPTH: usize> Circuit<Fp>
+    for MerkleCircuit<S, WIDTH, RATE, DEPTH>
+where
+    S: Spec<Fp, WIDTH, RATE> + Copy + Clone,
+{
+    type Config = MerkleConfig<WIDTH, RATE>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            leaf: Value::unknown(),
+            siblings: [Value::unknown(); DEPTH],
+            indices: [Value::unknown(); DEPTH],
+            _spec: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MerkleConfig::configure::<S>(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let leaf = config
+            .hash
+            .load_message(layouter.namespace(|| "load leaf"), &[self.leaf])?
+            .remove(0);
+        let root = config.assign_root::<S>(
+            layouter.namespace(|| "path"),
+            leaf,
+            &self.siblings,
+            &self.indices,
+        )?;
+
+        layouter.constrain_instance(root.cell(), config.hash.expected, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MySpec;
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 10;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const DEPTH: usize = 4;
+
+    type TestSpec = MySpec<WIDTH, RATE>;
+    type Tree = MerkleTree<TestSpec, WIDTH, RATE>;
+    type TestCircuit = MerkleCircuit<TestSpec, WIDTH, RATE, DEPTH>;
+
+    fn tree() -> Tree {
+        let leaves = (0..11u64).map(|i| Fp::from(100 + i)).collect::<Vec<_>>();
+        Tree::new(DEPTH, &leaves)
+    }
+
+    #[test]
+    fn test_tree_paths() {
+        let tree = tree();
+        assert_eq!(tree.depth(), DEPTH);
+        for index in [0, 5, 10, 15] {
+            let path = tree.path(index);
+            assert_eq!(path.depth(), DEPTH);
+            assert_eq!(
+                path.root::<TestSpec, WIDTH, RATE>(tree.leaf(index)),
+                tree.root()
+            );
+        }
+        assert_eq!(tree.leaf(15), Fp::ZERO);
+    }
+
+    #[test]
+    fn test_circuit_accepts_members() {
+        let tree = tree();
+        for index in [0, 6, 9, 15] {
+            let circuit = TestCircuit::new(tree.leaf(index), &tree.path(index));
+            let prover = MockProver::run(K, &circuit, vec![vec![tree.root()]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "leaf {}", index);
+        }
+    }
+
+    #[test]
+    fn test_circuit_rejects_non_members() {
+        let tree = tree();
+        let circuit = TestCircuit::new(Fp::from(99), &tree.path(3));
+        let prover = MockProver::run(K, &circuit, vec![vec![tree.root()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let mut path = tree.path(3);
+        path.indices[1] = !path.indices[1];
+        let circuit = TestCircuit::new(tree.leaf(3), &path);
+        let prover = MockProver::run(K, &circuit, vec![vec![tree.root()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_circuit_rejects_non_boolean_index() {
+        let tree = tree();
+        let mut circuit = TestCircuit::new(tree.leaf(2), &tree.path(2));
+        circuit.indices[0] = Value::known(Fp::from(2));
+        let prover = MockProver::run(K, &circuit, vec![vec![tree.root()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
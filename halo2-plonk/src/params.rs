@@ -0,0 +1,288 @@
+//! Poseidon parameter sets.
+//!
+//! The round constants and the MDS matrix of a spec are fixed by its field,
+//! width and round numbers: `generate_constants` draws them from the Grain
+//! LFSR seeded with these values, as the reference `generate_parameters_grain`
+//! script does, and `secure_mds() = 0` takes the first Cauchy matrix it draws.
+//! What remains to choose are the round numbers, which [`round_numbers`]
+//! derives from the security bounds of the Poseidon paper.
+use halo2curves::bn256::Fr as Fp;
+use halo2curves::ff::{Field, FromUniformBytes, PrimeField};
+
+use halo2_poseidon::poseidon::primitives::{generate_constants, Mds, Spec};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+pub use halo2_poseidon::poseidon::primitives::P128Pow5T3;
+
+use crate::variable_length::permute;
+
+/// Security level in bits of the derived specs.
+pub const SECURITY_LEVEL: usize = 128;
+
+/// Partial rounds of circomlib's Poseidon for widths 2 to 17, with 8 full
+/// rounds. They come from an earlier version of the round number script and
+/// are at least the ones [`round_numbers`] derives.
+pub const CIRCOM_PARTIAL_ROUNDS: [usize; 16] = [
+    56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundNumbers {
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+}
+
+/// Cheapest round numbers, in S-boxes, for which the permutation of `width`
+/// words with the S-box `x^alpha` over a field of `field_bits` bits and
+/// modulus `2^log2_modulus` resists the statistical, interpolation and
+/// Gröbner basis attacks at `security_level` bits, following the reference
+/// `calc_round_numbers.py` including the bound of eprint 2023/537. As in the
+/// reference, two full rounds and 7.5% of the partial rounds are added as a
+/// security margin.
+// `Option::is_none_or` needs Rust 1.82
+#[allow(clippy::unnecessary_map_or)]
+pub fn round_numbers(
+    field_bits: u32,
+    log2_modulus: f64,
+    width: usize,
+    alpha: u64,
+    security_level: usize,
+) -> RoundNumbers {
+    assert!(width >= 2, "Poseidon needs a width of at least 2");
+    assert!(alpha >= 3, "Only the x^alpha S-box is supported");
+
+    let mut best: Option<(usize, RoundNumbers)> = None;
+    for partial_rounds in 1..500 {
+        let full_rounds = (4..100).step_by(2).find(|full_rounds| {
+            is_secure(
+                field_bits as f64,
+                log2_modulus,
+                width as f64,
+                *full_rounds as f64,
+                partial_rounds as f64,
+                alpha as f64,
+                security_level as f64,
+            )
+        });
+        if let Some(full_rounds) = full_rounds {
+            let rounds = RoundNumbers {
+                full_rounds: full_rounds + 2,
+                partial_rounds: (partial_rounds as f64 * 1.075).ceil() as usize,
+            };
+            let cost = width * rounds.full_rounds + rounds.partial_rounds;
+            if best.map_or(true, |(best_cost, _)| cost < best_cost) {
+                best = Some((cost, rounds));
+            }
+        }
+    }
+    best.expect("No secure round numbers").1
+}
+
+/// [`round_numbers`] for the field `F`.
+pub fn round_numbers_for<F: PrimeField>(
+    width: usize,
+    alpha: u64,
+    security_level: usize,
+) -> RoundNumbers {
+    let modulus = F::MODULUS.trim_start_matches("0x");
+    let modulus = modulus
+        .chars()
+        .map(|c| c.to_digit(16).expect("Hexadecimal modulus"))
+        .fold(0.0, |acc, digit| acc * 16.0 + digit as f64);
+    round_numbers(F::NUM_BITS, modulus.log2(), width, alpha, security_level)
+}
+
+fn is_secure(n: f64, log2_p: f64, t: f64, r_f: f64, r_p: f64, alpha: f64, m: f64) -> bool {
+    let log_alpha_2 = 1.0 / alpha.log2();
+
+    // statistical
+    let r_f_1 = if m <= (log2_p - (alpha - 1.0) / 2.0).floor() * (t + 1.0) {
+        6.0
+    } else {
+        10.0
+    };
+    // interpolation
+    let r_f_2 = 1.0 + (log_alpha_2 * m.min(n)).ceil() + (t.ln() / alpha.ln()).ceil() - r_p;
+    // Gröbner basis
+    let r_f_3 = log_alpha_2 * m.min(log2_p) - r_p;
+    let r_f_4 = t - 1.0 + log_alpha_2 * (m / (t + 1.0)).min(log2_p / 2.0) - r_p;
+    let r_f_5 = (t - 2.0 + m / (2.0 * alpha.log2()) - r_p) / (t - 1.0);
+    let r_f_max = [r_f_1, r_f_2, r_f_3, r_f_4, r_f_5]
+        .into_iter()
+        .map(f64::ceil)
+        .fold(f64::MIN, f64::max);
+    if r_f < r_f_max {
+        return false;
+    }
+
+    // eprint 2023/537, with the exponent 2 rather than 2.3727
+    let r = (t / 3.0).floor();
+    let over = (r_f - 1.0) * t + r_p + r + r * (r_f / 2.0) + r_p + alpha;
+    let under = r * (r_f / 2.0) + r_p + alpha;
+    (2.0 * log2_binomial(over as u64, under as u64)).ceil() >= m
+}
+
+fn log2_binomial(n: u64, k: u64) -> f64 {
+    let k = k.min(n - k);
+    (1..=k)
+        .map(|i| ((n - k + i) as f64 / i as f64).log2())
+        .sum()
+}
+
+/// Round numbers of `F` and `width` at [`SECURITY_LEVEL`] with `x^5`,
+/// computed once per field and width.
+fn pow5_round_numbers<F: PrimeField>(width: usize) -> RoundNumbers {
+    static CACHE: OnceLock<Mutex<HashMap<(&'static str, usize), RoundNumbers>>> = OnceLock::new();
+    *CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry((F::MODULUS, width))
+        .or_insert_with(|| round_numbers_for::<F>(width, 5, SECURITY_LEVEL))
+}
+
+/// Poseidon with the `x^5` S-box and the round numbers derived for the field
+/// and width at [`SECURITY_LEVEL`]. Over the Pasta fields with a width of 3 it
+/// is [`P128Pow5T3`].
+#[derive(Debug, Clone, Copy)]
+pub struct Pow5Spec<const WIDTH: usize, const RATE: usize>;
+
+impl<F, const WIDTH: usize, const RATE: usize> Spec<F, WIDTH, RATE> for Pow5Spec<WIDTH, RATE>
+where
+    F: FromUniformBytes<64> + Ord,
+{
+    fn full_rounds() -> usize {
+        pow5_round_numbers::<F>(WIDTH).full_rounds
+    }
+
+    fn partial_rounds() -> usize {
+        pow5_round_numbers::<F>(WIDTH).partial_rounds
+    }
+
+    fn sbox(val: F) -> F {
+        val.pow_vartime([5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[F; WIDTH]>, Mds<F, WIDTH>, Mds<F, WIDTH>) {
+        generate_constants::<_, Self, WIDTH, RATE>()
+    }
+}
+
+/// The parameters of circomlib's Poseidon over BN254, for `WIDTH` from 2 to
+/// 17 and `RATE = WIDTH - 1`.
+#[derive(Debug, Clone, Copy)]
+pub struct CircomBn254<const WIDTH: usize, const RATE: usize>;
+
+impl<const WIDTH: usize, const RATE: usize> Spec<Fp, WIDTH, RATE> for CircomBn254<WIDTH, RATE> {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        CIRCOM_PARTIAL_ROUNDS[WIDTH - 2]
+    }
+
+    fn sbox(val: Fp) -> Fp {
+        val.pow_vartime([5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[Fp; WIDTH]>, Mds<Fp, WIDTH>, Mds<Fp, WIDTH>) {
+        generate_constants::<_, Self, WIDTH, RATE>()
+    }
+}
+
+/// circomlib's `Poseidon(RATE)` template: a single permutation of the inputs
+/// behind a zero capacity word, without padding or domain separation, which
+/// is why it differs from the `ConstantLength` hash with the same spec.
+pub fn circom_hash<const WIDTH: usize, const RATE: usize>(inputs: [Fp; RATE]) -> Fp {
+    assert_eq!(WIDTH, RATE + 1, "circom hashes WIDTH - 1 inputs");
+
+    let (round_constants, mds, _) = CircomBn254::<WIDTH, RATE>::constants();
+    let mut state = [Fp::ZERO; WIDTH];
+    state[1..].copy_from_slice(&inputs);
+    permute::<_, CircomBn254<WIDTH, RATE>, WIDTH, RATE>(&mut state, &mds, &round_constants);
+    state[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MySpec;
+    use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+    use halo2curves::pasta;
+
+    /// Parses big-endian hex as printed by circomlibjs.
+    fn fp(hex: &str) -> Fp {
+        let hex = hex.trim_start_matches("0x");
+        let mut repr = [0u8; 32];
+        for (i, byte) in repr.iter_mut().rev().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        Fp::from_repr(repr).unwrap()
+    }
+
+    #[test]
+    fn test_round_numbers() {
+        let pasta = round_numbers_for::<pasta::Fp>(3, 5, 128);
+        assert_eq!(
+            pasta,
+            RoundNumbers {
+                full_rounds: <P128Pow5T3 as Spec<pasta::Fp, 3, 2>>::full_rounds(),
+                partial_rounds: <P128Pow5T3 as Spec<pasta::Fp, 3, 2>>::partial_rounds(),
+            }
+        );
+        assert_eq!(pasta.partial_rounds, 56);
+
+        for width in 2..18 {
+            let rounds = round_numbers_for::<Fp>(width, 5, 128);
+            assert_eq!(rounds.full_rounds, 8);
+            assert_eq!(rounds.partial_rounds, if width < 6 { 56 } else { 57 });
+            assert!(CIRCOM_PARTIAL_ROUNDS[width - 2] >= rounds.partial_rounds);
+        }
+    }
+
+    #[test]
+    fn test_pow5_spec_matches_p128pow5t3() {
+        let message = [pasta::Fp::from(1), pasta::Fp::from(2)];
+        let expected =
+            poseidon::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash(message);
+        let output =
+            poseidon::Hash::<_, Pow5Spec<3, 2>, ConstantLength<2>, 3, 2>::init().hash(message);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_my_spec_is_derived_spec() {
+        let message = [Fp::from(1), Fp::from(2)];
+        assert_eq!(
+            poseidon::Hash::<_, MySpec<3, 2>, ConstantLength<2>, 3, 2>::init().hash(message),
+            poseidon::Hash::<_, Pow5Spec<3, 2>, ConstantLength<2>, 3, 2>::init().hash(message),
+        );
+    }
+
+    #[test]
+    fn test_circom_vectors() {
+        // circomlibjs poseidon([1]), poseidon([1, 2]) and poseidon([1, 2, 3, 4])
+        assert_eq!(
+            circom_hash::<2, 1>([Fp::from(1)]),
+            fp("0x29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133")
+        );
+        assert_eq!(
+            circom_hash::<3, 2>([Fp::from(1), Fp::from(2)]),
+            fp("0x115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a")
+        );
+        assert_eq!(
+            circom_hash::<5, 4>([Fp::from(1), Fp::from(2), Fp::from(3), Fp::from(4)]),
+            fp("0x299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465")
+        );
+    }
+}
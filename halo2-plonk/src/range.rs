@@ -0,0 +1,409 @@
+//! Range checks and comparisons of field elements as integers.
+//!
+//! A value is in `[0, 2^bits)` when it splits into `LIMB_BITS`-bit limbs, each
+//! looked up in a table of `[0, 2^LIMB_BITS)`. The limbs are taken off a
+//! running sum, one row per limb:
+//!
+//! | running_sum | shift | q_lookup | q_last |
+//! | z_0 = value |       | 1        | 0      |
+//! | z_1         |       | 1        | 0      |
+//! | ...         |       |          |        |
+//! | z_{m-1}     | 2^s   | 0        | 1      |
+//!
+//! where `z_i = limb_i + 2^LIMB_BITS z_{i+1}` and the last limb is `z_{m-1}`
+//! itself. When `bits` is not a multiple of `LIMB_BITS`, the last limb has
+//! `r = LIMB_BITS - s` bits, which holds when both it and `2^s` times it are in
+//! the table.
+//!
+//! The table takes `2^LIMB_BITS` rows, so a circuit range checking with 8-bit
+//! limbs needs `K >= 9`.
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+
+use halo2curves::bn256::Fr as Fp;
+use halo2curves::ff::{Field, PrimeField};
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone)]
+pub struct RangeCheckConfig<const LIMB_BITS: usize> {
+    running_sum: Column<Advice>,
+    shift: Column<Fixed>,
+    q_lookup: Selector,
+    q_last: Selector,
+    table: TableColumn,
+}
+
+impl<const LIMB_BITS: usize> RangeCheckConfig<LIMB_BITS> {
+    pub fn configure(meta: &mut ConstraintSystem<Fp>) -> Self {
+        assert!(
+            (1..=16).contains(&LIMB_BITS),
+            "The table of 2^LIMB_BITS rows must fit in the circuit"
+        );
+
+        let running_sum = meta.advice_column();
+        meta.enable_equality(running_sum);
+        let shift = meta.fixed_column();
+        let q_lookup = meta.complex_selector();
+        let q_last = meta.complex_selector();
+        let table = meta.lookup_table_column();
+
+        let limb = |meta: &mut VirtualCells<'_, Fp>| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let q_last = meta.query_selector(q_last);
+            let z_cur = meta.query_advice(running_sum, Rotation::cur());
+            let z_next = meta.query_advice(running_sum, Rotation::next());
+            let radix = Expression::Constant(Fp::from(1 << LIMB_BITS));
+
+            q_lookup * (z_cur.clone() - z_next * radix) + q_last * z_cur
+        };
+        meta.lookup("limb", |meta| vec![(limb(meta), table)]);
+        meta.lookup("shifted last limb", |meta| {
+            let shift = meta.query_fixed(shift, Rotation::cur());
+            vec![(limb(meta) * shift, table)]
+        });
+
+        Self {
+            running_sum,
+            shift,
+            q_lookup,
+            q_last,
+            table,
+        }
+    }
+
+    /// Fills the lookup table, once per circuit.
+    pub fn load_table(&self, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "range check table",
+            |mut table| {
+                for value in 0..1 << LIMB_BITS {
+                    table.assign_cell(
+                        || format!("{}", value),
+                        self.table,
+                        value,
+                        || Value::known(Fp::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Constrains `value` to `[0, 2^bits)`.
+    pub fn range_check(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        value: &AssignedCell<Fp, Fp>,
+        bits: usize,
+    ) -> Result<(), Error> {
+        // the limbs must not wrap around the modulus
+        assert!(
+            (1..Fp::NUM_BITS as usize).contains(&bits),
+            "Cannot range check {} bits",
+            bits
+        );
+        let limbs = bits.div_ceil(LIMB_BITS);
+        let shift = Fp::from(2).pow_vartime([(limbs * LIMB_BITS - bits) as u64]);
+
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                let mut z = value.copy_advice(|| "z_0", &mut region, self.running_sum, 0)?;
+                for i in 1..limbs {
+                    self.q_lookup.enable(&mut region, i - 1)?;
+                    let next = z.value().map(|z| shift_right(*z, LIMB_BITS));
+                    z = region.assign_advice(
+                        || format!("z_{}", i),
+                        self.running_sum,
+                        i,
+                        || next,
+                    )?;
+                }
+                self.q_last.enable(&mut region, limbs - 1)?;
+                region.assign_fixed(|| "shift", self.shift, limbs - 1, || Value::known(shift))?;
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Compares two values known to be in `[0, 2^bits)`: with `lt` boolean,
+///
+/// | a | b | low | lt |
+///
+/// `low = a - b + 2^bits lt` is in `[0, 2^bits)` only for `lt = (a < b)`.
+#[derive(Debug, Clone)]
+pub struct LessThanConfig<const LIMB_BITS: usize> {
+    range: RangeCheckConfig<LIMB_BITS>,
+    advice: [Column<Advice>; 4],
+    s_less_than: Selector,
+    bits: usize,
+}
+
+impl<const LIMB_BITS: usize> LessThanConfig<LIMB_BITS> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        range: RangeCheckConfig<LIMB_BITS>,
+        bits: usize,
+    ) -> Self {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let s_less_than = meta.selector();
+
+        meta.create_gate("less than", |meta| {
+            let s = meta.query_selector(s_less_than);
+            let [a, b, low, lt] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(Fp::ONE);
+            let range = Expression::Constant(Fp::from(2).pow_vartime([bits as u64]));
+
+            vec![
+                s.clone() * lt.clone() * (one - lt.clone()),
+                s * (low - a + b - lt * range),
+            ]
+        });
+
+        Self {
+            range,
+            advice,
+            s_less_than,
+            bits,
+        }
+    }
+
+    /// Returns the cell holding one if `a < b` and zero otherwise. Both values
+    /// must be range checked to the `bits` of the config beforehand.
+    pub fn less_than(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: &AssignedCell<Fp, Fp>,
+        b: &AssignedCell<Fp, Fp>,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let range = Fp::from(2).pow_vartime([self.bits as u64]);
+        let (low, lt) = layouter.assign_region(
+            || "less than",
+            |mut region| {
+                self.s_less_than.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, self.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, self.advice[1], 0)?;
+
+                let lt = a
+                    .value()
+                    .zip(b.value())
+                    .map(|(a, b)| Fp::from((compare(a, b) == Ordering::Less) as u64));
+                let low = a
+                    .value()
+                    .zip(b.value())
+                    .zip(lt)
+                    .map(|((a, b), lt)| *a - b + lt * range);
+                Ok((
+                    region.assign_advice(|| "low", self.advice[2], 0, || low)?,
+                    region.assign_advice(|| "lt", self.advice[3], 0, || lt)?,
+                ))
+            },
+        )?;
+
+        self.range
+            .range_check(layouter.namespace(|| "low"), &low, self.bits)?;
+        Ok(lt)
+    }
+}
+
+/// Compares the canonical integer representatives of `a` and `b`.
+fn compare(a: &Fp, b: &Fp) -> Ordering {
+    let (a, b) = (a.to_repr(), b.to_repr());
+    a.as_ref().iter().rev().cmp(b.as_ref().iter().rev())
+}
+
+/// The integer quotient of `value` by `2^bits`, for `bits < 64`.
+fn shift_right(value: Fp, bits: usize) -> Fp {
+    let low = u64::from_le_bytes(value.to_repr().as_ref()[..8].try_into().unwrap());
+    let low = Fp::from(low & ((1 << bits) - 1));
+    // exact, as value - low is a multiple of 2^bits
+    (value - low) * Fp::from(1 << bits).invert().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MyConfig, MySpec};
+    use halo2_poseidon::poseidon::{
+        primitives::{self as poseidon, ConstantLength},
+        Hash, Pow5Chip,
+    };
+    use halo2_proofs::circuit::SimpleFloorPlanner;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::Circuit;
+
+    const K: u32 = 8;
+    const LIMB_BITS: usize = 4;
+    const BITS: usize = 10;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+
+    #[derive(Clone)]
+    struct TestConfig {
+        hash: MyConfig<WIDTH, RATE>,
+        range: RangeCheckConfig<LIMB_BITS>,
+        less_than: LessThanConfig<LIMB_BITS>,
+    }
+
+    /// Range checks `value` to `bits` bits and `a` and `b` to [`BITS`] bits,
+    /// and exposes the hash of `(a, b)` and whether `a < b`, next to the
+    /// Poseidon chip.
+    #[derive(Clone, Copy, Default)]
+    struct TestCircuit {
+        value: Value<Fp>,
+        bits: usize,
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl TestCircuit {
+        fn new(value: Fp, bits: usize, a: Fp, b: Fp) -> Self {
+            Self {
+                value: Value::known(value),
+                bits,
+                a: Value::known(a),
+                b: Value::known(b),
+            }
+        }
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                bits: self.bits,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let hash = MyConfig::configure::<MySpec<WIDTH, RATE>>(meta);
+            let range = RangeCheckConfig::configure(meta);
+            let less_than = LessThanConfig::configure(meta, range.clone(), BITS);
+            TestConfig {
+                hash,
+                range,
+                less_than,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.range.load_table(layouter.namespace(|| "table"))?;
+            let cells = config.hash.load_message(
+                layouter.namespace(|| "load message"),
+                &[self.value, self.a, self.b],
+            )?;
+            for (i, (cell, bits)) in cells.iter().zip([self.bits, BITS, BITS]).enumerate() {
+                config.range.range_check(
+                    layouter.namespace(|| format!("range_{}", i)),
+                    cell,
+                    bits,
+                )?;
+            }
+            let lt = config.less_than.less_than(
+                layouter.namespace(|| "less than"),
+                &cells[1],
+                &cells[2],
+            )?;
+
+            let chip = Pow5Chip::construct(config.hash.poseidon_config.clone());
+            let hasher = Hash::<_, _, MySpec<WIDTH, RATE>, ConstantLength<2>, WIDTH, RATE>::init(
+                chip,
+                layouter.namespace(|| "init"),
+            )?;
+            let output = hasher.hash(
+                layouter.namespace(|| "hash"),
+                [cells[1].clone(), cells[2].clone()],
+            )?;
+
+            layouter.constrain_instance(output.cell(), config.hash.expected, 0)?;
+            layouter.constrain_instance(lt.cell(), config.hash.expected, 1)
+        }
+    }
+
+    fn verify_bits(value: Fp, bits: usize, a: Fp, b: Fp, lt: bool) -> bool {
+        let hash = poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<2>, WIDTH, RATE>::init()
+            .hash([a, b]);
+        let instance = vec![hash, Fp::from(lt as u64)];
+        MockProver::run(K, &TestCircuit::new(value, bits, a, b), vec![instance])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    fn verify(value: Fp, a: Fp, b: Fp, lt: bool) -> bool {
+        verify_bits(value, BITS, a, b, lt)
+    }
+
+    #[test]
+    fn test_range_check_boundaries() {
+        let max = Fp::from((1 << BITS) - 1);
+        let (a, b) = (Fp::from(1), Fp::from(2));
+
+        assert!(verify(Fp::ZERO, a, b, true));
+        assert!(verify(Fp::from(1 << (BITS - 1)), a, b, true));
+        assert!(verify(max, a, b, true));
+
+        // first value out of range, and the largest field element
+        assert!(!verify(max + Fp::ONE, a, b, true));
+        assert!(!verify(-Fp::ONE, a, b, true));
+        // in range of the full limbs only
+        assert!(!verify(Fp::from(1 << (LIMB_BITS * 3 - 1)), a, b, true));
+    }
+
+    #[test]
+    fn test_range_check_whole_limbs() {
+        let verify =
+            |value: u64| verify_bits(Fp::from(value), 2 * LIMB_BITS, Fp::ZERO, Fp::ONE, true);
+        assert!(verify(0));
+        assert!(verify(255));
+        assert!(!verify(256));
+    }
+
+    #[test]
+    fn test_less_than_boundaries() {
+        let max = (1 << BITS) - 1;
+        for (a, b) in [(0, 1), (0, max), (max - 1, max), (41, 42)] {
+            let (a, b) = (Fp::from(a), Fp::from(b));
+            assert!(verify(Fp::ZERO, a, b, true));
+            assert!(!verify(Fp::ZERO, a, b, false));
+            assert!(verify(Fp::ZERO, b, a, false));
+            assert!(!verify(Fp::ZERO, b, a, true));
+        }
+        for a in [0, 1, max] {
+            let a = Fp::from(a);
+            assert!(verify(Fp::ZERO, a, a, false));
+            assert!(!verify(Fp::ZERO, a, a, true));
+        }
+    }
+
+    #[test]
+    fn test_shift_right() {
+        assert_eq!(shift_right(Fp::from(0x1234), 4), Fp::from(0x123));
+        assert_eq!(shift_right(Fp::from(u64::MAX), 8), Fp::from(u64::MAX >> 8));
+        let large = Fp::from_raw([0, 1, 0, 0]);
+        assert_eq!(shift_right(large, 4), Fp::from(1 << 60));
+        assert_eq!(
+            compare(&Fp::from(2), &-Fp::ONE),
+            Ordering::Less,
+            "p - 1 is the largest value"
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! Semaphore-style anonymous signalling.
+//!
+//! A member's identity commitment `Poseidon(secret, nullifier)` is a leaf of a
+//! public Merkle tree. To signal once per topic (the external nullifier), the
+//! member proves membership without revealing the leaf and publishes the
+//! nullifier hash `Poseidon(nullifier, external_nullifier)`, which is the same
+//! on every proof for the topic, so that a [`NullifierRegistry`] can reject a
+//! second signal.
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use halo2curves::bn256::Fr as Fp;
+use halo2curves::ff::PrimeField;
+
+use halo2_poseidon::poseidon::primitives::Spec;
+use std::collections::HashSet;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::merkle::{hash_pair, MerkleConfig, MerklePath};
+
+/// Row of each public input in the instance column.
+pub const ROOT_ROW: usize = 0;
+pub const NULLIFIER_HASH_ROW: usize = 1;
+pub const EXTERNAL_NULLIFIER_ROW: usize = 2;
+pub const SIGNAL_ROW: usize = 3;
+
+/// The leaf committing to a member's secret and nullifier.
+pub fn identity_commitment<S, const WIDTH: usize, const RATE: usize>(
+    secret: Fp,
+    nullifier: Fp,
+) -> Fp
+where
+    S: Spec<Fp, WIDTH, RATE>,
+{
+    hash_pair::<S, WIDTH, RATE>(secret, nullifier)
+}
+
+/// The value revealed when signalling on `external_nullifier`.
+pub fn nullifier_hash<S, const WIDTH: usize, const RATE: usize>(
+    nullifier: Fp,
+    external_nullifier: Fp,
+) -> Fp
+where
+    S: Spec<Fp, WIDTH, RATE>,
+{
+    hash_pair::<S, WIDTH, RATE>(nullifier, external_nullifier)
+}
+
+/// Public inputs of a [`SemaphoreCircuit`] proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicInputs {
+    pub root: Fp,
+    pub nullifier_hash: Fp,
+    pub external_nullifier: Fp,
+    pub signal: Fp,
+}
+
+impl PublicInputs {
+    /// The instance column, in row order.
+    pub fn to_instance(&self) -> Vec<Fp> {
+        vec![
+            self.root,
+            self.nullifier_hash,
+            self.external_nullifier,
+            self.signal,
+        ]
+    }
+}
+
+/// Proves that the prover knows the secret and nullifier of a leaf under the
+/// public root, that the public nullifier hash is derived from that nullifier
+/// and the public external nullifier, and binds the public signal to the proof.
+#[derive(Clone, Copy)]
+pub struct SemaphoreCircuit<S, const WIDTH: usize, const RATE: usize, const DEPTH: usize>
+where
+    S: Spec<Fp, WIDTH, RATE> + Clone + Copy,
+{
+    secret: Value<Fp>,
+    nullifier: Value<Fp>,
+    siblings: [Value<Fp>; DEPTH],
+    indices: [Value<Fp>; DEPTH],
+    external_nullifier: Value<Fp>,
+    signal: Value<Fp>,
+    _spec: PhantomData<S>,
+}
+
+impl<S, const WIDTH: usize, const RATE: usize, const DEPTH: usize>
+    SemaphoreCircuit<S, WIDTH, RATE, DEPTH>
+where
+    S: Spec<Fp, WIDTH, RATE> + Clone + Copy,
+{
+    pub fn new(
+        secret: Fp,
+        nullifier: Fp,
+        path: &MerklePath,
+        external_nullifier: Fp,
+        signal: Fp,
+    ) -> Self {
+        assert_eq!(path.depth(), DEPTH, "Invalid path depth");
+        Self {
+            secret: Value::known(secret),
+            nullifier: Value::known(nullifier),
+            siblings: std::array::from_fn(|i| Value::known(path.siblings[i])),
+            indices: std::array::from_fn(|i| Value::known(Fp::from(path.indices[i] as u64))),
+            external_nullifier: Value::known(external_nullifier),
+            signal: Value::known(signal),
+            _spec: PhantomData,
+        }
+    }
+}
+
+impl<S, const WIDTH: usize, const RATE: usize, const DEPTH: usize> Circuit<Fp>
+    for SemaphoreCircuit<S, WIDTH, RATE, DEPTH>
+where
+    S: Spec<Fp, WIDTH, RATE> + Copy + Clone,
+{
+    type Config = MerkleConfig<WIDTH, RATE>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            secret: Value::unknown(),
+            nullifier: Value::unknown(),
+            siblings: [Value::unknown(); DEPTH],
+            indices: [Value::unknown(); DEPTH],
+            external_nullifier: Value::unknown(),
+            signal: Value::unknown(),
+            _spec: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MerkleConfig::configure::<S>(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let inputs = config.hash.load_message(
+            layouter.namespace(|| "load inputs"),
+            &[
+                self.secret,
+                self.nullifier,
+                self.external_nullifier,
+                self.signal,
+            ],
+        )?;
+        let [secret, nullifier, external_nullifier, signal]: [_; 4] = inputs.try_into().unwrap();
+
+        let commitment = config.hash_pair::<S>(
+            layouter.namespace(|| "identity commitment"),
+            secret,
+            nullifier.clone(),
+        )?;
+        let root = config.assign_root::<S>(
+            layouter.namespace(|| "membership"),
+            commitment,
+            &self.siblings,
+            &self.indices,
+        )?;
+        let nullifier_hash = config.hash_pair::<S>(
+            layouter.namespace(|| "nullifier hash"),
+            nullifier,
+            external_nullifier.clone(),
+        )?;
+
+        // the signal takes no part in any gate; the copy constraint to the
+        // instance is what ties the proof to it
+        let expected = config.hash.expected;
+        layouter.constrain_instance(root.cell(), expected, ROOT_ROW)?;
+        layouter.constrain_instance(nullifier_hash.cell(), expected, NULLIFIER_HASH_ROW)?;
+        layouter.constrain_instance(external_nullifier.cell(), expected, EXTERNAL_NULLIFIER_ROW)?;
+        layouter.constrain_instance(signal.cell(), expected, SIGNAL_ROW)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// The proof is for another tree than the registry's
+    UnknownRoot,
+    /// The member already signalled on this external nullifier
+    NullifierReused,
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::UnknownRoot => write!(f, "unknown Merkle root"),
+            RegistryError::NullifierReused => write!(f, "nullifier hash already used"),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// Nullifier hashes seen so far for a group. Register the public inputs of a
+/// proof only once the proof has been verified.
+#[derive(Clone, Debug)]
+pub struct NullifierRegistry {
+    root: Fp,
+    used: HashSet<[u8; 32]>,
+}
+
+impl NullifierRegistry {
+    pub fn new(root: Fp) -> Self {
+        Self {
+            root,
+            used: HashSet::new(),
+        }
+    }
+
+    pub fn root(&self) -> Fp {
+        self.root
+    }
+
+    /// Moves to the root of the updated group. Nullifier hashes stay used,
+    /// since members keep their nullifier across updates.
+    pub fn update_root(&mut self, root: Fp) {
+        self.root = root;
+    }
+
+    pub fn is_used(&self, nullifier_hash: Fp) -> bool {
+        self.used.contains(&nullifier_hash.to_repr())
+    }
+
+    /// Records the nullifier hash of a verified proof, rejecting a proof for
+    /// another root or a reused nullifier hash.
+    pub fn register(&mut self, inputs: &PublicInputs) -> Result<(), RegistryError> {
+        if inputs.root != self.root {
+            return Err(RegistryError::UnknownRoot);
+        }
+        if !self.used.insert(inputs.nullifier_hash.to_repr()) {
+            return Err(RegistryError::NullifierReused);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::MerkleTree;
+    use crate::MySpec;
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 10;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const DEPTH: usize = 3;
+
+    type TestSpec = MySpec<WIDTH, RATE>;
+    type TestCircuit = SemaphoreCircuit<TestSpec, WIDTH, RATE, DEPTH>;
+
+    struct Member {
+        secret: Fp,
+        nullifier: Fp,
+    }
+
+    fn group() -> (Vec<Member>, MerkleTree<TestSpec, WIDTH, RATE>) {
+        let members = (0..5u64)
+            .map(|i| Member {
+                secret: Fp::from(1000 + i),
+                nullifier: Fp::from(2000 + i),
+            })
+            .collect::<Vec<_>>();
+        let leaves = members
+            .iter()
+            .map(|m| identity_commitment::<TestSpec, WIDTH, RATE>(m.secret, m.nullifier))
+            .collect::<Vec<_>>();
+        (members, MerkleTree::new(DEPTH, &leaves))
+    }
+
+    fn signal(
+        tree: &MerkleTree<TestSpec, WIDTH, RATE>,
+        index: usize,
+        member: &Member,
+        external_nullifier: Fp,
+        signal: Fp,
+    ) -> (TestCircuit, PublicInputs) {
+        let circuit = TestCircuit::new(
+            member.secret,
+            member.nullifier,
+            &tree.path(index),
+            external_nullifier,
+            signal,
+        );
+        let inputs = PublicInputs {
+            root: tree.root(),
+            nullifier_hash: nullifier_hash::<TestSpec, WIDTH, RATE>(
+                member.nullifier,
+                external_nullifier,
+            ),
+            external_nullifier,
+            signal,
+        };
+        (circuit, inputs)
+    }
+
+    #[test]
+    fn test_members_can_signal() {
+        let (members, tree) = group();
+        for (index, member) in members.iter().enumerate() {
+            let (circuit, inputs) = signal(&tree, index, member, Fp::from(7), Fp::from(1));
+            let prover = MockProver::run(K, &circuit, vec![inputs.to_instance()]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "member {}", index);
+        }
+    }
+
+    #[test]
+    fn test_rejects_wrong_public_inputs() {
+        let (members, tree) = group();
+        let (circuit, inputs) = signal(&tree, 2, &members[2], Fp::from(7), Fp::from(1));
+
+        let tampered = [
+            PublicInputs {
+                root: inputs.root + Fp::from(1),
+                ..inputs
+            },
+            PublicInputs {
+                nullifier_hash: nullifier_hash::<TestSpec, WIDTH, RATE>(
+                    members[2].nullifier,
+                    Fp::from(8),
+                ),
+                ..inputs
+            },
+            PublicInputs {
+                external_nullifier: Fp::from(8),
+                ..inputs
+            },
+            PublicInputs {
+                signal: Fp::from(0),
+                ..inputs
+            },
+        ];
+        for inputs in tampered {
+            let prover = MockProver::run(K, &circuit, vec![inputs.to_instance()]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_rejects_non_member() {
+        let (members, tree) = group();
+        let outsider = Member {
+            secret: Fp::from(3000),
+            nullifier: members[1].nullifier,
+        };
+        let (circuit, inputs) = signal(&tree, 1, &outsider, Fp::from(7), Fp::from(1));
+        let prover = MockProver::run(K, &circuit, vec![inputs.to_instance()]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_registry_rejects_double_signal() {
+        let (members, tree) = group();
+        let mut registry = NullifierRegistry::new(tree.root());
+
+        let (_, first) = signal(&tree, 0, &members[0], Fp::from(7), Fp::from(1));
+        let (_, second) = signal(&tree, 0, &members[0], Fp::from(7), Fp::from(0));
+        let (_, other_topic) = signal(&tree, 0, &members[0], Fp::from(8), Fp::from(1));
+        let (_, other_member) = signal(&tree, 1, &members[1], Fp::from(7), Fp::from(1));
+
+        assert_eq!(registry.register(&first), Ok(()));
+        assert!(registry.is_used(first.nullifier_hash));
+        assert_eq!(
+            registry.register(&second),
+            Err(RegistryError::NullifierReused)
+        );
+        assert_eq!(registry.register(&other_topic), Ok(()));
+        assert_eq!(registry.register(&other_member), Ok(()));
+
+        registry.update_root(Fp::from(1));
+        assert_eq!(
+            registry.register(&other_member),
+            Err(RegistryError::UnknownRoot)
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Files for the KZG parameters, the keys and proofs, so that setup, proving
+//! and verifying can run in separate processes.
+//!
+//! Parameters use halo2's own format. Verifying keys are stored in the
+//! compressed, validated `Processed` format and proving keys, which are large
+//! and only read by the prover, as `RawBytes`. A proof file holds the public
+//! inputs followed by the proof:
+//!
+//! | magic `b"H2PF"` | n: u32 LE | n field elements, 32 bytes LE | len: u32 LE | proof |
+use halo2_proofs::{
+    plonk::{Circuit, ProvingKey, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+
+use halo2curves::bn256::{Bn256, Fr as Fp, G1Affine};
+use halo2curves::ff::PrimeField;
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const PROOF_MAGIC: &[u8; 4] = b"H2PF";
+
+pub fn write_params(path: impl AsRef<Path>, params: &ParamsKZG<Bn256>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    params.write(&mut writer)?;
+    writer.flush()
+}
+
+pub fn read_params(path: impl AsRef<Path>) -> io::Result<ParamsKZG<Bn256>> {
+    ParamsKZG::read(&mut BufReader::new(File::open(path)?))
+}
+
+pub fn write_vk(path: impl AsRef<Path>, vk: &VerifyingKey<G1Affine>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    vk.write(&mut writer, SerdeFormat::Processed)?;
+    writer.flush()
+}
+
+/// Reads a verifying key written by [`write_vk`] for the circuit type `C`,
+/// whose configuration determines the shape of the key.
+pub fn read_vk<C: Circuit<Fp>>(path: impl AsRef<Path>) -> io::Result<VerifyingKey<G1Affine>> {
+    VerifyingKey::read::<_, C>(
+        &mut BufReader::new(File::open(path)?),
+        SerdeFormat::Processed,
+    )
+}
+
+pub fn write_pk(path: impl AsRef<Path>, pk: &ProvingKey<G1Affine>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    pk.write(&mut writer, SerdeFormat::RawBytes)?;
+    writer.flush()
+}
+
+pub fn read_pk<C: Circuit<Fp>>(path: impl AsRef<Path>) -> io::Result<ProvingKey<G1Affine>> {
+    ProvingKey::read::<_, C>(
+        &mut BufReader::new(File::open(path)?),
+        SerdeFormat::RawBytes,
+    )
+}
+
+/// A proof along with the public inputs of its single instance column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofFile {
+    pub public_inputs: Vec<Fp>,
+    pub proof: Vec<u8>,
+}
+
+impl ProofFile {
+    pub fn new(public_inputs: Vec<Fp>, proof: Vec<u8>) -> Self {
+        Self {
+            public_inputs,
+            proof,
+        }
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(PROOF_MAGIC)?;
+        write_len(writer, self.public_inputs.len())?;
+        for input in &self.public_inputs {
+            writer.write_all(input.to_repr().as_ref())?;
+        }
+        write_len(writer, self.proof.len())?;
+        writer.write_all(&self.proof)
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != PROOF_MAGIC {
+            return Err(invalid_data("not a proof file"));
+        }
+
+        let public_inputs = (0..read_len(reader)?)
+            .map(|_| {
+                let mut repr = [0u8; 32];
+                reader.read_exact(&mut repr)?;
+                Option::from(Fp::from_repr(repr))
+                    .ok_or_else(|| invalid_data("public input is not a canonical field element"))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        // the length is untrusted, so the proof grows with the bytes actually read
+        let len = read_len(reader)?;
+        let mut proof = vec![];
+        reader.take(len as u64).read_to_end(&mut proof)?;
+        if proof.len() != len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "proof is shorter than its length",
+            ));
+        }
+        Ok(Self {
+            public_inputs,
+            proof,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+fn write_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| invalid_data("length does not fit in a u32"))?;
+    writer.write_all(&len.to_le_bytes())
+}
+
+fn read_len<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    Ok(u32::from_le_bytes(len) as usize)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HashCircuit, MySpec};
+    use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+    use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof};
+    use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+    use halo2_proofs::transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    };
+    use rand_core::OsRng;
+    use std::path::PathBuf;
+
+    const K: u32 = 8;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const L: usize = 2;
+
+    type TestCircuit = HashCircuit<MySpec<WIDTH, RATE>, WIDTH, RATE, L>;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("plonk-example-{}-{}", std::process::id(), name))
+    }
+
+    fn verify(params: &ParamsKZG<Bn256>, vk: &VerifyingKey<G1Affine>, proof: &ProofFile) -> bool {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof.proof[..]);
+        verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params,
+            vk,
+            SingleStrategy::new(params),
+            &[&[&proof.public_inputs]],
+            &mut transcript,
+        )
+        .is_ok()
+    }
+
+    #[test]
+    fn test_proof_file_round_trip() {
+        let proof = ProofFile::new(vec![Fp::from(3), -Fp::from(1)], vec![1, 2, 3, 4, 5]);
+        let mut bytes = vec![];
+        proof.write(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 4 + 4 + 2 * 32 + 4 + 5);
+        assert_eq!(ProofFile::read(&mut &bytes[..]).unwrap(), proof);
+
+        bytes[0] = b'X';
+        assert!(ProofFile::read(&mut &bytes[..]).is_err());
+        bytes[0] = b'H';
+        bytes[8..40].copy_from_slice(&[0xff; 32]);
+        assert!(ProofFile::read(&mut &bytes[..]).is_err());
+        assert!(ProofFile::read(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_proof_file_layout() {
+        let proof = ProofFile::new(vec![Fp::from(3), Fp::from(0x0201)], vec![0xaa, 0xbb]);
+        let mut bytes = vec![];
+        proof.write(&mut bytes).unwrap();
+
+        let mut expected = b"H2PF".to_vec();
+        expected.extend([2, 0, 0, 0]);
+        expected.extend([3].iter().chain(&[0; 31]));
+        expected.extend([1, 2].iter().chain(&[0; 30]));
+        expected.extend([2, 0, 0, 0, 0xaa, 0xbb]);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_proof_file_with_oversized_length() {
+        // a length of 4 GiB followed by two bytes fails without allocating it
+        let mut bytes = b"H2PF".to_vec();
+        bytes.extend([0, 0, 0, 0]);
+        bytes.extend(u32::MAX.to_le_bytes());
+        bytes.extend([1, 2]);
+        let err = ProofFile::read(&mut &bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_prove_and_verify_across_files() {
+        let message = [Fp::from(475023450948321098), Fp::from(42)];
+        let commitment =
+            poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+                .hash(message);
+
+        // setup
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let circuit = TestCircuit::new(message);
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        let (params_path, vk_path, pk_path, proof_path) = (
+            temp_path("params"),
+            temp_path("vk"),
+            temp_path("pk"),
+            temp_path("proof"),
+        );
+        write_params(&params_path, &params).unwrap();
+        write_vk(&vk_path, &vk).unwrap();
+        write_pk(&pk_path, &pk).unwrap();
+
+        // prover
+        let params = read_params(&params_path).unwrap();
+        let pk = read_pk::<TestCircuit>(&pk_path).unwrap();
+        let public_inputs = vec![commitment];
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<_, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[&public_inputs]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        ProofFile::new(public_inputs, transcript.finalize())
+            .save(&proof_path)
+            .unwrap();
+
+        // verifier
+        let params = read_params(&params_path).unwrap();
+        let vk = read_vk::<TestCircuit>(&vk_path).unwrap();
+        let mut proof = ProofFile::load(&proof_path).unwrap();
+        assert!(verify(&params, &vk, &proof));
+        proof.public_inputs[0] += Fp::from(1);
+        assert!(!verify(&params, &vk, &proof));
+
+        for path in [params_path, vk_path, pk_path, proof_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,383 @@
+//! Transcripts: the hash that turns the prover's messages into the verifier's
+//! challenges. A proof only verifies with the transcript it was made with.
+//!
+//! [`Blake2b`] is halo2's default and the fastest natively. [`Keccak`] is the
+//! transcript the Solidity verifier of [`crate::evm`] recomputes, over Bn256
+//! only. [`Poseidon`] hashes with [`MySpec`] over the scalar field, which is
+//! cheap to recompute in a circuit verifying the proof.
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, Transcript, TranscriptRead,
+    TranscriptReadBuffer, TranscriptWrite, TranscriptWriterBuffer,
+};
+
+use halo2curves::bn256::G1Affine;
+use halo2curves::ff::{Field, FromUniformBytes, PrimeField};
+use halo2curves::group::GroupEncoding;
+use halo2curves::{Coordinates, CurveAffine};
+
+use halo2_poseidon::poseidon::primitives::{Mds, Spec};
+use halo2_solidity_verifier::{ChallengeEvm, Keccak256Transcript};
+
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use crate::variable_length::permute;
+use crate::MySpec;
+
+/// A transcript hash for proofs with commitments on `C`, by the types that
+/// write and read them.
+pub trait TranscriptHash<C: CurveAffine> {
+    type Challenge: EncodedChallenge<C>;
+    type Writer: TranscriptWriterBuffer<Vec<u8>, C, Self::Challenge>;
+    type Reader<'a>: TranscriptReadBuffer<&'a [u8], C, Self::Challenge>;
+
+    const NAME: &'static str;
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Blake2b;
+
+impl<C: CurveAffine> TranscriptHash<C> for Blake2b
+where
+    C::ScalarExt: FromUniformBytes<64>,
+{
+    type Challenge = Challenge255<C>;
+    type Writer = Blake2bWrite<Vec<u8>, C, Challenge255<C>>;
+    type Reader<'a> = Blake2bRead<&'a [u8], C, Challenge255<C>>;
+
+    const NAME: &'static str = "Blake2b";
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keccak;
+
+impl TranscriptHash<G1Affine> for Keccak {
+    type Challenge = ChallengeEvm<G1Affine>;
+    type Writer = Keccak256Transcript<G1Affine, Vec<u8>>;
+    type Reader<'a> = Keccak256Transcript<G1Affine, &'a [u8]>;
+
+    const NAME: &'static str = "Keccak-256";
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Poseidon;
+
+impl<C: CurveAffine> TranscriptHash<C> for Poseidon
+where
+    C::ScalarExt: FromUniformBytes<64> + Ord,
+{
+    type Challenge = PoseidonChallenge<C>;
+    type Writer = PoseidonWrite<Vec<u8>, C>;
+    type Reader<'a> = PoseidonRead<&'a [u8], C>;
+
+    const NAME: &'static str = "Poseidon";
+}
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+/// A duplex sponge over `MySpec<3, 2>`. Squeezing pads the words absorbed
+/// since the last squeeze with a one, absorbs them and returns the first
+/// word of the state.
+#[derive(Clone, Debug)]
+struct Sponge<F: Field> {
+    state: [F; WIDTH],
+    pending: Vec<F>,
+    round_constants: Vec<[F; WIDTH]>,
+    mds: Mds<F, WIDTH>,
+}
+
+impl<F: FromUniformBytes<64> + Ord> Sponge<F> {
+    fn new() -> Self {
+        let (round_constants, mds, _) = <MySpec<WIDTH, RATE> as Spec<F, WIDTH, RATE>>::constants();
+        Self {
+            state: [F::ZERO; WIDTH],
+            pending: vec![],
+            round_constants,
+            mds,
+        }
+    }
+
+    fn absorb(&mut self, word: F) {
+        self.pending.push(word);
+    }
+
+    /// Absorbs a point as the two 128-bit halves of each coordinate, and the
+    /// identity, which has none, as zeros.
+    fn absorb_point<C: CurveAffine<ScalarExt = F>>(&mut self, point: &C) {
+        let coordinates: Option<Coordinates<C>> = point.coordinates().into();
+        match coordinates {
+            Some(coordinates) => {
+                for coordinate in [coordinates.x(), coordinates.y()] {
+                    let repr = coordinate.to_repr();
+                    for half in repr.as_ref().chunks(16) {
+                        let mut bytes = [0u8; 16];
+                        bytes[..half.len()].copy_from_slice(half);
+                        self.absorb(F::from_u128(u128::from_le_bytes(bytes)));
+                    }
+                }
+            }
+            None => (0..4).for_each(|_| self.absorb(F::ZERO)),
+        }
+    }
+
+    fn squeeze(&mut self) -> F {
+        self.pending.push(F::ONE);
+        for block in self.pending.chunks(RATE) {
+            for (word, value) in self.state.iter_mut().zip(block) {
+                *word += value;
+            }
+            permute::<_, MySpec<WIDTH, RATE>, WIDTH, RATE>(
+                &mut self.state,
+                &self.mds,
+                &self.round_constants,
+            );
+        }
+        self.pending.clear();
+        self.state[0]
+    }
+}
+
+/// A challenge squeezed as a scalar, without reduction.
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonChallenge<C: CurveAffine>(C::ScalarExt);
+
+impl<C: CurveAffine> EncodedChallenge<C> for PoseidonChallenge<C> {
+    type Input = C::ScalarExt;
+
+    fn new(challenge_input: &Self::Input) -> Self {
+        Self(*challenge_input)
+    }
+
+    fn get_scalar(&self) -> C::ScalarExt {
+        self.0
+    }
+}
+
+/// Writes a proof with the [`Poseidon`] transcript. Points are written in
+/// their compressed encoding and scalars as their representation.
+#[derive(Debug)]
+pub struct PoseidonWrite<W: Write, C: CurveAffine> {
+    sponge: Sponge<C::ScalarExt>,
+    writer: W,
+    _marker: PhantomData<C>,
+}
+
+impl<W: Write, C: CurveAffine> Transcript<C, PoseidonChallenge<C>> for PoseidonWrite<W, C>
+where
+    C::ScalarExt: FromUniformBytes<64> + Ord,
+{
+    fn squeeze_challenge(&mut self) -> PoseidonChallenge<C> {
+        PoseidonChallenge::new(&self.sponge.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.sponge.absorb_point(&point);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::ScalarExt) -> io::Result<()> {
+        self.sponge.absorb(scalar);
+        Ok(())
+    }
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWrite<C, PoseidonChallenge<C>> for PoseidonWrite<W, C>
+where
+    C::ScalarExt: FromUniformBytes<64> + Ord,
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        self.writer.write_all(point.to_bytes().as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: C::ScalarExt) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        self.writer.write_all(scalar.to_repr().as_ref())
+    }
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, PoseidonChallenge<C>>
+    for PoseidonWrite<W, C>
+where
+    C::ScalarExt: FromUniformBytes<64> + Ord,
+{
+    fn init(writer: W) -> Self {
+        Self {
+            sponge: Sponge::new(),
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    fn finalize(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads a proof written by [`PoseidonWrite`].
+#[derive(Debug)]
+pub struct PoseidonRead<R: Read, C: CurveAffine> {
+    sponge: Sponge<C::ScalarExt>,
+    reader: R,
+    _marker: PhantomData<C>,
+}
+
+impl<R: Read, C: CurveAffine> Transcript<C, PoseidonChallenge<C>> for PoseidonRead<R, C>
+where
+    C::ScalarExt: FromUniformBytes<64> + Ord,
+{
+    fn squeeze_challenge(&mut self) -> PoseidonChallenge<C> {
+        PoseidonChallenge::new(&self.sponge.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.sponge.absorb_point(&point);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::ScalarExt) -> io::Result<()> {
+        self.sponge.absorb(scalar);
+        Ok(())
+    }
+}
+
+impl<R: Read, C: CurveAffine> TranscriptRead<C, PoseidonChallenge<C>> for PoseidonRead<R, C>
+where
+    C::ScalarExt: FromUniformBytes<64> + Ord,
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed))
+            .ok_or_else(|| io::Error::other("invalid point encoding in proof"))?;
+        self.common_point(point)?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::ScalarExt> {
+        let mut data = <C::ScalarExt as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar: C::ScalarExt = Option::from(C::ScalarExt::from_repr(data))
+            .ok_or_else(|| io::Error::other("invalid field element encoding in proof"))?;
+        self.common_scalar(scalar)?;
+        Ok(scalar)
+    }
+}
+
+impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, PoseidonChallenge<C>>
+    for PoseidonRead<R, C>
+where
+    C::ScalarExt: FromUniformBytes<64> + Ord,
+{
+    fn init(reader: R) -> Self {
+        Self {
+            sponge: Sponge::new(),
+            reader,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Backend, Ipa, Kzg};
+    use crate::HashCircuit;
+    use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+    use halo2_proofs::plonk::ProvingKey;
+
+    const K: u32 = 8;
+    const L: usize = 2;
+
+    fn message<F: PrimeField>() -> [F; L] {
+        [F::from(475023450948321098), F::from(42)]
+    }
+
+    fn commitment<F: FromUniformBytes<64> + Ord>() -> F {
+        poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init()
+            .hash(message())
+    }
+
+    /// Keys for the hash circuit, and a proof with the transcript `T`.
+    fn prove<B: Backend, T: TranscriptHash<B::Curve>>(
+        params: &B::Params,
+    ) -> (ProvingKey<B::Curve>, Vec<u8>)
+    where
+        MySpec<WIDTH, RATE>: Spec<B::Scalar, WIDTH, RATE>,
+    {
+        let circuit = HashCircuit::<MySpec<WIDTH, RATE>, WIDTH, RATE, L, B::Scalar>::new(message());
+        let pk = B::keygen(params, &circuit).unwrap();
+        let proof = B::prove_with::<T, _>(params, &pk, circuit, &[commitment()]).unwrap();
+        (pk, proof)
+    }
+
+    fn verifies<B: Backend, T: TranscriptHash<B::Curve>>(
+        params: &B::Params,
+        pk: &ProvingKey<B::Curve>,
+        proof: &[u8],
+    ) -> bool {
+        B::verify_with::<T>(params, pk.get_vk(), &[commitment()], proof).is_ok()
+    }
+
+    #[test]
+    fn test_kzg_transcripts() {
+        let params = Kzg::setup(K);
+
+        let (pk, proof) = prove::<Kzg, Blake2b>(&params);
+        assert!(verifies::<Kzg, Blake2b>(&params, &pk, &proof));
+        assert!(!verifies::<Kzg, Keccak>(&params, &pk, &proof));
+        assert!(!verifies::<Kzg, Poseidon>(&params, &pk, &proof));
+
+        let (pk, proof) = prove::<Kzg, Keccak>(&params);
+        assert!(!verifies::<Kzg, Blake2b>(&params, &pk, &proof));
+        assert!(verifies::<Kzg, Keccak>(&params, &pk, &proof));
+        assert!(!verifies::<Kzg, Poseidon>(&params, &pk, &proof));
+
+        let (pk, proof) = prove::<Kzg, Poseidon>(&params);
+        assert!(!verifies::<Kzg, Blake2b>(&params, &pk, &proof));
+        assert!(!verifies::<Kzg, Keccak>(&params, &pk, &proof));
+        assert!(verifies::<Kzg, Poseidon>(&params, &pk, &proof));
+    }
+
+    #[test]
+    fn test_ipa_transcripts() {
+        let params = Ipa::setup(K);
+
+        let (pk, proof) = prove::<Ipa, Blake2b>(&params);
+        assert!(verifies::<Ipa, Blake2b>(&params, &pk, &proof));
+        assert!(!verifies::<Ipa, Poseidon>(&params, &pk, &proof));
+
+        let (pk, proof) = prove::<Ipa, Poseidon>(&params);
+        assert!(!verifies::<Ipa, Blake2b>(&params, &pk, &proof));
+        assert!(verifies::<Ipa, Poseidon>(&params, &pk, &proof));
+    }
+
+    #[test]
+    fn test_poseidon_sponge() {
+        use halo2curves::bn256::Fr as Fp;
+        use halo2curves::group::prime::PrimeCurveAffine;
+
+        let mut sponge = Sponge::<Fp>::new();
+        let first = sponge.squeeze();
+        // squeezing again permutes further
+        assert_ne!(sponge.squeeze(), first);
+
+        // the padding separates absorbing a trailing zero
+        let mut with_zero = Sponge::<Fp>::new();
+        with_zero.absorb(Fp::ZERO);
+        assert_ne!(with_zero.squeeze(), first);
+
+        let mut generator = Sponge::<Fp>::new();
+        generator.absorb_point(&G1Affine::generator());
+        let mut again = Sponge::<Fp>::new();
+        again.absorb_point(&G1Affine::generator());
+        let generator = generator.squeeze();
+        assert_eq!(again.squeeze(), generator);
+        assert_ne!(generator, first);
+
+        let mut identity = Sponge::<Fp>::new();
+        identity.absorb_point(&G1Affine::identity());
+        assert_ne!(identity.squeeze(), generator);
+    }
+}
@@ -0,0 +1,311 @@
+//! Poseidon hashing of messages whose length is only fixed when the circuit is
+//! built, such as serialized records.
+//!
+//! `ConstantLength<L>` fixes the length in the type and separates domains by
+//! setting the capacity element to `L * 2^64`. [`VariableLength`] instead pads
+//! the message with a one followed by zeros up to a multiple of `RATE`, as in
+//! the variable-input-length mode of the Poseidon paper, so that no two
+//! messages share a padded form. Its capacity element `2^64 - 1` is not a
+//! multiple of `2^64` and never coincides with a constant-length domain.
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use halo2curves::bn256::Fr as Fp;
+use halo2curves::ff::{Field, PrimeField};
+
+use halo2_poseidon::poseidon::{
+    primitives::{Absorbing, Domain, Mds, Spec},
+    PaddedWord, Pow5Chip, Sponge,
+};
+use std::marker::PhantomData;
+
+use crate::MyConfig;
+
+/// Number of bytes packed into one message word; 31 bytes always fit below
+/// the Bn256 scalar field modulus.
+pub const BYTES_PER_WORD: usize = 31;
+
+/// Domain of messages with an arbitrary number of words.
+#[derive(Clone, Copy, Debug)]
+pub struct VariableLength;
+
+impl<F: PrimeField, const RATE: usize> Domain<F, RATE> for VariableLength {
+    type Padding = Vec<F>;
+
+    fn name() -> String {
+        "VariableLength".to_string()
+    }
+
+    fn initial_capacity_element() -> F {
+        F::from_u128(u64::MAX as u128)
+    }
+
+    fn padding(input_len: usize) -> Self::Padding {
+        let mut padding = vec![F::ZERO; RATE - input_len % RATE];
+        padding[0] = F::ONE;
+        padding
+    }
+}
+
+/// Hashes `message` outside the circuit, with the same result as
+/// [`VariableLengthHashCircuit`].
+pub fn hash<S, const WIDTH: usize, const RATE: usize>(message: &[Fp]) -> Fp
+where
+    S: Spec<Fp, WIDTH, RATE>,
+{
+    sponge_hash::<S, VariableLength, WIDTH, RATE>(message)
+}
+
+/// Packs `bytes` little-endian into words of [`BYTES_PER_WORD`] bytes, after
+/// a terminating `0x01` byte. Without it, inputs differing only in trailing
+/// zero bytes would pack to the same words.
+pub fn bytes_to_words(bytes: &[u8]) -> Vec<Fp> {
+    let mut bytes = bytes.to_vec();
+    bytes.push(1);
+    bytes
+        .chunks(BYTES_PER_WORD)
+        .map(|chunk| {
+            let mut repr = [0u8; 32];
+            repr[..chunk.len()].copy_from_slice(chunk);
+            Fp::from_repr(repr).unwrap()
+        })
+        .collect()
+}
+
+/// Absorbs the padded message `RATE` words per permutation and squeezes the
+/// first word of the state.
+fn sponge_hash<S, D, const WIDTH: usize, const RATE: usize>(message: &[Fp]) -> Fp
+where
+    S: Spec<Fp, WIDTH, RATE>,
+    D: Domain<Fp, RATE>,
+{
+    let (round_constants, mds, _) = S::constants();
+    let mut state = [Fp::ZERO; WIDTH];
+    state[RATE] = D::initial_capacity_element();
+
+    let padded = message
+        .iter()
+        .copied()
+        .chain(D::padding(message.len()))
+        .collect::<Vec<_>>();
+    for block in padded.chunks(RATE) {
+        for (word, value) in state.iter_mut().zip(block) {
+            *word += value;
+        }
+        permute::<_, S, WIDTH, RATE>(&mut state, &mds, &round_constants);
+    }
+    state[0]
+}
+
+/// The Poseidon permutation: half of the full rounds, the partial rounds
+/// (S-box on the first word only), then the other half of the full rounds.
+pub(crate) fn permute<F, S, const WIDTH: usize, const RATE: usize>(
+    state: &mut [F; WIDTH],
+    mds: &Mds<F, WIDTH>,
+    round_constants: &[[F; WIDTH]],
+) where
+    F: Field,
+    S: Spec<F, WIDTH, RATE>,
+{
+    let half_full_rounds = S::full_rounds() / 2;
+    let partial_rounds = half_full_rounds..half_full_rounds + S::partial_rounds();
+
+    for (round, constants) in round_constants.iter().enumerate() {
+        for (word, constant) in state.iter_mut().zip(constants) {
+            *word += constant;
+        }
+        if partial_rounds.contains(&round) {
+            state[0] = S::sbox(state[0]);
+        } else {
+            for word in state.iter_mut() {
+                *word = S::sbox(*word);
+            }
+        }
+
+        let mut mixed = [F::ZERO; WIDTH];
+        for (word, row) in mixed.iter_mut().zip(mds.iter()) {
+            *word = row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum();
+        }
+        *state = mixed;
+    }
+}
+
+/// Proves knowledge of a message of a given number of words hashing to the
+/// public instance. The length is part of the circuit shape, so keys are
+/// generated per length.
+#[derive(Clone)]
+pub struct VariableLengthHashCircuit<S, const WIDTH: usize, const RATE: usize>
+where
+    S: Spec<Fp, WIDTH, RATE> + Clone + Copy,
+{
+    message: Vec<Value<Fp>>,
+    _spec: PhantomData<S>,
+}
+
+impl<S, const WIDTH: usize, const RATE: usize> VariableLengthHashCircuit<S, WIDTH, RATE>
+where
+    S: Spec<Fp, WIDTH, RATE> + Clone + Copy,
+{
+    pub fn new(message: &[Fp]) -> Self {
+        Self {
+            message: message.iter().copied().map(Value::known).collect(),
+            _spec: PhantomData,
+        }
+    }
+
+    /// Circuit for messages of `len` words, without witnesses, for key
+    /// generation.
+    pub fn with_length(len: usize) -> Self {
+        Self {
+            message: vec![Value::unknown(); len],
+            _spec: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.message.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.message.is_empty()
+    }
+}
+
+impl<S, const WIDTH: usize, const RATE: usize> Circuit<Fp>
+    for VariableLengthHashCircuit<S, WIDTH, RATE>
+where
+    S: Spec<Fp, WIDTH, RATE> + Copy + Clone,
+{
+    type Config = MyConfig<WIDTH, RATE>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::with_length(self.len())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyConfig::configure::<S>(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = Pow5Chip::construct(config.poseidon_config.clone());
+        let message = config.load_message(layouter.namespace(|| "load message"), &self.message)?;
+
+        let mut sponge = Sponge::<
+            Fp,
+            Pow5Chip<Fp, WIDTH, RATE>,
+            S,
+            Absorbing<PaddedWord<Fp>, RATE>,
+            VariableLength,
+            WIDTH,
+            RATE,
+        >::new(chip, layouter.namespace(|| "init"))?;
+
+        let padding = <VariableLength as Domain<Fp, RATE>>::padding(self.len());
+        let words = message
+            .into_iter()
+            .map(PaddedWord::Message)
+            .chain(padding.into_iter().map(PaddedWord::Padding));
+        for (i, word) in words.enumerate() {
+            sponge.absorb(layouter.namespace(|| format!("absorb_{}", i)), word)?;
+        }
+        let output = sponge
+            .finish_absorbing(layouter.namespace(|| "finish absorbing"))?
+            .squeeze(layouter.namespace(|| "squeeze"))?;
+
+        layouter.constrain_instance(output.cell(), config.expected, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MySpec;
+    use halo2_poseidon::poseidon::primitives::{self as poseidon, ConstantLength};
+    use halo2_proofs::dev::MockProver;
+
+    const K: u32 = 10;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+
+    type TestCircuit = VariableLengthHashCircuit<MySpec<WIDTH, RATE>, WIDTH, RATE>;
+
+    fn message(len: usize) -> Vec<Fp> {
+        (0..len as u64).map(|i| Fp::from(i * i + 7)).collect()
+    }
+
+    #[test]
+    fn test_sponge_matches_constant_length() {
+        let message = [Fp::from(1), Fp::from(2), Fp::from(3)];
+        let expected =
+            poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<3>, WIDTH, RATE>::init()
+                .hash(message);
+        let output = sponge_hash::<MySpec<WIDTH, RATE>, ConstantLength<3>, WIDTH, RATE>(&message);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_padding_separates_lengths() {
+        let digest = hash::<MySpec<WIDTH, RATE>, WIDTH, RATE>;
+        assert_ne!(digest(&[]), digest(&[Fp::ZERO]));
+        assert_ne!(digest(&[Fp::ONE]), digest(&[Fp::ONE, Fp::ZERO]));
+        assert_ne!(
+            digest(&message(2)),
+            poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<2>, WIDTH, RATE>::init()
+                .hash(message(2).try_into().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_circuit_accepts_any_length() {
+        for len in [0, 1, RATE, RATE + 1, 7] {
+            let message = message(len);
+            let digest = hash::<MySpec<WIDTH, RATE>, WIDTH, RATE>(&message);
+            let prover =
+                MockProver::run(K, &TestCircuit::new(&message), vec![vec![digest]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "message of {} words", len);
+        }
+    }
+
+    #[test]
+    fn test_circuit_rejects_wrong_digest() {
+        let message = message(5);
+        let digest = hash::<MySpec<WIDTH, RATE>, WIDTH, RATE>(&message[..4]);
+        let prover = MockProver::run(K, &TestCircuit::new(&message), vec![vec![digest]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_bytes_to_words() {
+        let bytes = (0..40u8).collect::<Vec<_>>();
+        let words = bytes_to_words(&bytes);
+        assert_eq!(words.len(), 2);
+        let mut tail = [0u8; 16];
+        tail[..9].copy_from_slice(&bytes[31..]);
+        tail[9] = 1;
+        assert_eq!(words[1], Fp::from_u128(u128::from_le_bytes(tail)));
+
+        assert_eq!(bytes_to_words(&[]), vec![Fp::ONE]);
+        // the terminator of a whole word of bytes takes a word of its own
+        assert_eq!(
+            bytes_to_words(&[0; BYTES_PER_WORD]),
+            vec![Fp::ZERO, Fp::ONE]
+        );
+    }
+
+    #[test]
+    fn test_trailing_zero_bytes_change_the_digest() {
+        let digest =
+            |bytes: &[u8]| hash::<MySpec<WIDTH, RATE>, WIDTH, RATE>(&bytes_to_words(bytes));
+        assert_ne!(digest(b"a"), digest(b"a\0"));
+        assert_ne!(digest(b""), digest(b"\0"));
+        let word = [7u8; BYTES_PER_WORD];
+        assert_ne!(digest(&word), digest(&[&word[..], &[0]].concat()));
+    }
+}
@@ -0,0 +1,44 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_kzg::Kzg;
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use ark_serialize::CanonicalSerialize;
+use ark_std::{test_rng, UniformRand};
+use data_structs::fri::Fri;
+use sha2::Sha256;
+
+type Kzg381 = Kzg<Bls12_381>;
+type FriSha256 = Fri<Fr, Sha256>;
+
+fn main() {
+    let rng = &mut test_rng();
+    let blowup_factor = 4;
+    let num_queries = 32;
+
+    println!("degree | KZG proof (bytes) | FRI proof (bytes)");
+    for log_degree in [4, 8, 12] {
+        let degree = 1 << log_degree;
+        let poly = DensePolynomial::<Fr>::rand(degree, rng);
+        let point = Fr::rand(rng);
+        let value = poly.evaluate(&point);
+
+        let pp = Kzg381::setup(degree, rng).expect("KZG setup failed");
+        let (powers, vk) = Kzg381::trim(&pp, degree).expect("KZG trim failed");
+        let kzg_commitment = Kzg381::commit(&powers, &poly).expect("KZG commit failed");
+        let kzg_proof = Kzg381::open(&powers, &poly, point).expect("KZG open failed");
+        Kzg381::verify(&vk, &kzg_commitment, point, value, &kzg_proof).expect("KZG verify failed");
+
+        let params =
+            FriSha256::setup(degree, blowup_factor, num_queries).expect("FRI setup failed");
+        let fri_commitment = FriSha256::commit(&params, &poly).expect("FRI commit failed");
+        let fri_proof = FriSha256::open(&params, &poly, point).expect("FRI open failed");
+        FriSha256::verify(&params, &fri_commitment, point, value, &fri_proof)
+            .expect("FRI verify failed");
+
+        println!(
+            "{:>6} | {:>17} | {:>17}",
+            degree,
+            kzg_proof.w.compressed_size(),
+            fri_proof.size_in_bytes()
+        );
+    }
+}
@@ -0,0 +1,560 @@
+//! FRI low-degree test and the polynomial commitment built on top of it.
+//!
+//! A polynomial is committed to by a Merkle tree over its evaluations on a
+//! multiplicative coset of size `blowup_factor * max_degree`, where
+//! `max_degree` is a power of two. To open it at `z` with value `v`, the prover
+//! runs FRI on the quotient `q(X) = (f(X) - v) / (X - z)`, which has degree
+//! below `max_degree` exactly when `f` has degree at most `max_degree`. Folding
+//! only tests power-of-two bounds, hence the restriction on `max_degree`. The
+//! evaluations of `q` are never
+//! committed, the verifier derives them from the openings of `f` instead. Each
+//! FRI round folds the current layer with a random challenge, halving both the
+//! domain and the degree bound, until the polynomial is a constant that is sent
+//! in the clear. Every Merkle leaf holds the pair of evaluations at `x` and
+//! `-x`, so one authentication path per layer and query suffices.
+use crate::merkletree::{MerklePath, MerkleTree, TreeRoot};
+use ark_ff::{batch_inversion, PrimeField};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use digest::{Digest, Output};
+use std::fmt;
+use std::marker::PhantomData;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The maximum degree must be a power of two of at least 2
+    UnsupportedDegree(usize),
+    /// The blowup factor must be a power of two of at least 2
+    InvalidBlowupFactor(usize),
+    /// At least one query is needed
+    NoQueries,
+    /// The field has no multiplicative subgroup as large as the domain
+    DomainTooLarge,
+    /// The polynomial exceeds the maximum degree of the parameters
+    DegreeTooLarge { degree: usize, max_degree: usize },
+    /// Openings must be at points outside the evaluation domain
+    PointInDomain,
+    /// The proof does not verify
+    InvalidProof,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnsupportedDegree(degree) => write!(
+                f,
+                "maximum degree {} is not a power of two of at least 2",
+                degree
+            ),
+            Error::InvalidBlowupFactor(factor) => write!(
+                f,
+                "blowup factor {} is not a power of two of at least 2",
+                factor
+            ),
+            Error::NoQueries => write!(f, "at least one query is needed"),
+            Error::DomainTooLarge => write!(f, "field does not have a large enough subgroup"),
+            Error::DegreeTooLarge { degree, max_degree } => write!(
+                f,
+                "polynomial of degree {} exceeds the maximum degree {}",
+                degree, max_degree
+            ),
+            Error::PointInDomain => write!(f, "point lies in the evaluation domain"),
+            Error::InvalidProof => write!(f, "FRI proof does not verify"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub struct Fri<F: PrimeField, D: Digest> {
+    _field: PhantomData<F>,
+    _digest: PhantomData<D>,
+}
+
+pub struct FriParams<F: PrimeField> {
+    max_degree: usize,
+    num_queries: usize,
+    num_rounds: usize,
+    domain: Radix2EvaluationDomain<F>,
+}
+
+/// Merkle root of the evaluations of the committed polynomial.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FriCommitment<D: Digest>(pub Output<D>);
+
+/// Evaluation pairs and their authentication paths for every folded layer,
+/// starting with the committed polynomial itself.
+#[derive(Clone, Debug)]
+pub struct FriQuery<F: PrimeField, D: Digest> {
+    pub openings: Vec<(F, F, MerklePath<D>)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FriProof<F: PrimeField, D: Digest> {
+    pub layer_roots: Vec<Output<D>>,
+    pub queries: Vec<FriQuery<F, D>>,
+    pub final_value: F,
+}
+
+impl<F: PrimeField> FriParams<F> {
+    /// Polynomials of degree at most this verify.
+    pub fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    pub fn domain_size(&self) -> usize {
+        self.domain.size()
+    }
+
+    /// Returns the i-th element of the evaluation domain of layer `round`.
+    fn element(&self, round: usize, index: usize) -> F {
+        let exponent = [1u64 << round];
+        let offset = self.domain.coset_offset().pow(exponent);
+        let generator = self.domain.group_gen().pow(exponent);
+        offset * generator.pow([index as u64])
+    }
+}
+
+impl<F: PrimeField, D: Digest> FriProof<F, D> {
+    /// Number of bytes needed to send the proof, with field elements in
+    /// compressed form.
+    pub fn size_in_bytes(&self) -> usize {
+        let digest_size = <D as Digest>::output_size();
+        let field_size = self.final_value.compressed_size();
+        let queries: usize = self
+            .queries
+            .iter()
+            .flat_map(|query| &query.openings)
+            .map(|(_, _, path)| 2 * field_size + path.siblings.len() * digest_size)
+            .sum();
+        self.layer_roots.len() * digest_size + queries + field_size
+    }
+}
+
+impl<F: PrimeField, D: Digest> Fri<F, D> {
+    /// Creates parameters for polynomials of degree at most `max_degree`, a
+    /// power of two, evaluated on a domain `blowup_factor` times larger than
+    /// needed, with `num_queries` spot checks per proof.
+    pub fn setup(
+        max_degree: usize,
+        blowup_factor: usize,
+        num_queries: usize,
+    ) -> Result<FriParams<F>, Error> {
+        if max_degree < 2 || !max_degree.is_power_of_two() {
+            return Err(Error::UnsupportedDegree(max_degree));
+        }
+        if blowup_factor < 2 || !blowup_factor.is_power_of_two() {
+            return Err(Error::InvalidBlowupFactor(blowup_factor));
+        }
+        if num_queries == 0 {
+            return Err(Error::NoQueries);
+        }
+
+        let domain = max_degree
+            .checked_mul(blowup_factor)
+            .and_then(Radix2EvaluationDomain::new)
+            .and_then(|domain| domain.get_coset(F::GENERATOR))
+            .ok_or(Error::DomainTooLarge)?;
+        Ok(FriParams {
+            max_degree,
+            num_queries,
+            // the quotient has degree below `max_degree`
+            num_rounds: max_degree.trailing_zeros() as usize,
+            domain,
+        })
+    }
+
+    pub fn commit(
+        params: &FriParams<F>,
+        poly: &DensePolynomial<F>,
+    ) -> Result<FriCommitment<D>, Error> {
+        let evaluations = Self::evaluate(params, poly)?;
+        Ok(FriCommitment(layer_tree::<F, D>(&evaluations).root()))
+    }
+
+    /// Proves that `poly` evaluates to `poly.evaluate(point)` at `point`. The
+    /// evaluations and the Merkle tree of the commitment are recomputed.
+    pub fn open(
+        params: &FriParams<F>,
+        poly: &DensePolynomial<F>,
+        point: F,
+    ) -> Result<FriProof<F, D>, Error> {
+        if is_in_domain(params, point) {
+            return Err(Error::PointInDomain);
+        }
+
+        let evaluations = Self::evaluate(params, poly)?;
+        let value = poly.evaluate(&point);
+        Ok(Self::open_evaluations(params, evaluations, point, value))
+    }
+
+    /// Checks that the committed polynomial has degree at most the maximum
+    /// degree of `params` and evaluates to `value` at `point`.
+    pub fn verify(
+        params: &FriParams<F>,
+        commitment: &FriCommitment<D>,
+        point: F,
+        value: F,
+        proof: &FriProof<F, D>,
+    ) -> Result<(), Error> {
+        if is_in_domain(params, point) {
+            return Err(Error::PointInDomain);
+        }
+        if proof.layer_roots.len() + 1 != params.num_rounds
+            || proof.queries.len() != params.num_queries
+        {
+            return Err(Error::InvalidProof);
+        }
+
+        let mut transcript = Transcript::<D>::new(&commitment.0, point, value);
+        let mut betas = Vec::with_capacity(params.num_rounds);
+        for round in 0..params.num_rounds {
+            betas.push(transcript.challenge::<F>());
+            if let Some(root) = proof.layer_roots.get(round) {
+                transcript.absorb(root);
+            }
+        }
+        transcript.absorb(&field_bytes(&[proof.final_value]));
+
+        let two_inv = F::from(2u64).inverse().unwrap();
+        for query in &proof.queries {
+            if query.openings.len() != params.num_rounds {
+                return Err(Error::InvalidProof);
+            }
+
+            let mut position = transcript.index(params.domain_size());
+            let mut expected = None;
+            for (round, (a, b, path)) in query.openings.iter().enumerate() {
+                let half = params.domain_size() >> (round + 1);
+                let index = position % half;
+                let root = match round {
+                    0 => &commitment.0,
+                    _ => &proof.layer_roots[round - 1],
+                };
+                let depth = half.trailing_zeros() as usize;
+                if !TreeRoot::<D>::verify(root, depth, index, field_bytes(&[*a, *b]), path) {
+                    return Err(Error::InvalidProof);
+                }
+
+                let x = params.element(round, index);
+                let (a, b) = match round {
+                    0 => ((*a - value) / (x - point), (*b - value) / (-x - point)),
+                    _ => (*a, *b),
+                };
+                let current = if position < half { a } else { b };
+                if expected.is_some_and(|expected| expected != current) {
+                    return Err(Error::InvalidProof);
+                }
+
+                expected = Some(fold(a, b, x, betas[round], two_inv));
+                position = index;
+            }
+
+            if expected != Some(proof.final_value) {
+                return Err(Error::InvalidProof);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(params: &FriParams<F>, poly: &DensePolynomial<F>) -> Result<Vec<F>, Error> {
+        if poly.degree() > params.max_degree {
+            return Err(Error::DegreeTooLarge {
+                degree: poly.degree(),
+                max_degree: params.max_degree,
+            });
+        }
+        Ok(poly.evaluate_over_domain_by_ref(params.domain).evals)
+    }
+
+    fn open_evaluations(
+        params: &FriParams<F>,
+        evaluations: Vec<F>,
+        point: F,
+        value: F,
+    ) -> FriProof<F, D> {
+        let two_inv = F::from(2u64).inverse().unwrap();
+        let mut trees = vec![layer_tree::<F, D>(&evaluations)];
+        let mut transcript = Transcript::<D>::new(&trees[0].root(), point, value);
+
+        let mut denominators: Vec<F> = params.domain.elements().map(|x| x - point).collect();
+        batch_inversion(&mut denominators);
+        let quotient: Vec<F> = evaluations
+            .iter()
+            .zip(denominators)
+            .map(|(eval, denominator)| (*eval - value) * denominator)
+            .collect();
+
+        let mut layers = vec![evaluations];
+        let mut current = quotient;
+        for round in 0..params.num_rounds {
+            let beta = transcript.challenge::<F>();
+            let half = current.len() / 2;
+            current = (0..half)
+                .map(|i| {
+                    let x = params.element(round, i);
+                    fold(current[i], current[i + half], x, beta, two_inv)
+                })
+                .collect();
+
+            if round + 1 < params.num_rounds {
+                let tree = layer_tree::<F, D>(&current);
+                transcript.absorb(&tree.root());
+                trees.push(tree);
+                layers.push(current.clone());
+            }
+        }
+        let final_value = current[0];
+        transcript.absorb(&field_bytes(&[final_value]));
+
+        let queries = (0..params.num_queries)
+            .map(|_| {
+                let mut position = transcript.index(params.domain_size());
+                let openings = layers
+                    .iter()
+                    .zip(&trees)
+                    .map(|(layer, tree)| {
+                        let half = layer.len() / 2;
+                        let index = position % half;
+                        position = index;
+                        (layer[index], layer[index + half], tree.open(index))
+                    })
+                    .collect();
+                FriQuery { openings }
+            })
+            .collect();
+
+        FriProof {
+            layer_roots: trees[1..].iter().map(|tree| tree.root()).collect(),
+            queries,
+            final_value,
+        }
+    }
+}
+
+/// Computes `g(x^2)` from `f(x) = a` and `f(-x) = b`, where
+/// `f(X) = f_e(X^2) + X * f_o(X^2)` and `g = f_e + beta * f_o`.
+fn fold<F: PrimeField>(a: F, b: F, x: F, beta: F, two_inv: F) -> F {
+    (a + b) * two_inv + beta * (a - b) * two_inv / x
+}
+
+fn is_in_domain<F: PrimeField>(params: &FriParams<F>, point: F) -> bool {
+    let size = [params.domain_size() as u64];
+    point.pow(size) == params.domain.coset_offset().pow(size)
+}
+
+/// Commits to a layer with one leaf per pair of evaluations at `x` and `-x`.
+fn layer_tree<F: PrimeField, D: Digest>(evaluations: &[F]) -> MerkleTree<D> {
+    let (left, right) = evaluations.split_at(evaluations.len() / 2);
+    let leafs: Vec<Vec<u8>> = left
+        .iter()
+        .zip(right)
+        .map(|(a, b)| field_bytes(&[*a, *b]))
+        .collect();
+    MerkleTree::new(&leafs)
+}
+
+fn field_bytes<F: PrimeField>(elements: &[F]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for element in elements {
+        element
+            .serialize_compressed(&mut bytes)
+            .expect("Serialization into a vector does not fail");
+    }
+    bytes
+}
+
+/// Fiat-Shamir transcript hashing every message into a running state.
+struct Transcript<D: Digest> {
+    state: Output<D>,
+}
+
+impl<D: Digest> Transcript<D> {
+    fn new<F: PrimeField>(root: &Output<D>, point: F, value: F) -> Self {
+        let mut transcript = Transcript {
+            state: D::digest(b"FRI"),
+        };
+        transcript.absorb(root);
+        transcript.absorb(&field_bytes(&[point, value]));
+        transcript
+    }
+
+    fn absorb(&mut self, message: &[u8]) {
+        self.state = D::new()
+            .chain_update(&self.state)
+            .chain_update(message)
+            .finalize();
+    }
+
+    fn squeeze(&mut self) -> Output<D> {
+        self.absorb(b"challenge");
+        self.state.clone()
+    }
+
+    fn challenge<F: PrimeField>(&mut self) -> F {
+        F::from_le_bytes_mod_order(&self.squeeze())
+    }
+
+    fn index(&mut self, bound: usize) -> usize {
+        let bytes = self.squeeze();
+        let mut index = [0u8; 8];
+        index.copy_from_slice(&bytes[..8]);
+        (u64::from_le_bytes(index) % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_ff::Field;
+    use ark_poly::DenseUVPolynomial;
+    use ark_std::{test_rng, UniformRand};
+    use sha2::Sha256;
+
+    type FriSha256 = Fri<Fr, Sha256>;
+
+    #[test]
+    fn test_fold_halves_the_degree() {
+        let mut rng = test_rng();
+        let poly = DensePolynomial::<Fr>::rand(7, &mut rng);
+        let beta = Fr::rand(&mut rng);
+        let x = Fr::rand(&mut rng);
+        let two_inv = Fr::from(2u64).inverse().unwrap();
+
+        // g = f_e + beta * f_o, with f_e and f_o the even and odd coefficients
+        let folded = DensePolynomial::from_coefficients_vec(
+            poly.coeffs
+                .chunks(2)
+                .map(|pair| pair[0] + beta * pair[1])
+                .collect(),
+        );
+        let a = poly.evaluate(&x);
+        let b = poly.evaluate(&-x);
+        assert_eq!(fold(a, b, x, beta, two_inv), folded.evaluate(&(x * x)));
+    }
+
+    #[test]
+    fn test_open_and_verify() {
+        let mut rng = test_rng();
+        for max_degree in [2, 8, 16, 128] {
+            let params = FriSha256::setup(max_degree, 4, 8).unwrap();
+            for degree in [max_degree / 2, max_degree] {
+                let poly = DensePolynomial::rand(degree, &mut rng);
+                let commitment = FriSha256::commit(&params, &poly).unwrap();
+                let point = Fr::rand(&mut rng);
+                let proof = FriSha256::open(&params, &poly, point).unwrap();
+                assert!(FriSha256::verify(
+                    &params,
+                    &commitment,
+                    point,
+                    poly.evaluate(&point),
+                    &proof
+                )
+                .is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_setup_requires_power_of_two_degree() {
+        for max_degree in [0, 1, 3, 15, 100] {
+            assert_eq!(
+                FriSha256::setup(max_degree, 4, 8).err(),
+                Some(Error::UnsupportedDegree(max_degree))
+            );
+        }
+        assert_eq!(
+            FriSha256::setup(16, 3, 8).err(),
+            Some(Error::InvalidBlowupFactor(3))
+        );
+        assert_eq!(FriSha256::setup(16, 4, 0).err(), Some(Error::NoQueries));
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_value() {
+        let mut rng = test_rng();
+        let params = FriSha256::setup(32, 2, 16).unwrap();
+        let poly = DensePolynomial::rand(32, &mut rng);
+        let commitment = FriSha256::commit(&params, &poly).unwrap();
+        let point = Fr::rand(&mut rng);
+        let proof = FriSha256::open(&params, &poly, point).unwrap();
+        let value = poly.evaluate(&point) + Fr::from(1u64);
+        assert_eq!(
+            FriSha256::verify(&params, &commitment, point, value, &proof),
+            Err(Error::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_proof() {
+        let mut rng = test_rng();
+        let params = FriSha256::setup(16, 4, 8).unwrap();
+        let poly = DensePolynomial::rand(16, &mut rng);
+        let commitment = FriSha256::commit(&params, &poly).unwrap();
+        let point = Fr::rand(&mut rng);
+        let value = poly.evaluate(&point);
+        let proof = FriSha256::open(&params, &poly, point).unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.final_value += Fr::from(1u64);
+        assert!(FriSha256::verify(&params, &commitment, point, value, &tampered).is_err());
+
+        let mut tampered = proof.clone();
+        tampered.queries[0].openings[1].0 += Fr::from(1u64);
+        assert!(FriSha256::verify(&params, &commitment, point, value, &tampered).is_err());
+
+        let other = FriSha256::commit(&params, &DensePolynomial::rand(16, &mut rng)).unwrap();
+        assert!(FriSha256::verify(&params, &other, point, value, &proof).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_high_degree_evaluations() {
+        let mut rng = test_rng();
+        let params = FriSha256::setup(16, 4, 16).unwrap();
+        let evaluations: Vec<Fr> = (0..params.domain_size())
+            .map(|_| Fr::rand(&mut rng))
+            .collect();
+        let commitment = FriCommitment(layer_tree::<Fr, Sha256>(&evaluations).root());
+        let point = Fr::rand(&mut rng);
+        let value = Fr::rand(&mut rng);
+        let proof = FriSha256::open_evaluations(&params, evaluations, point, value);
+        assert!(FriSha256::verify(&params, &commitment, point, value, &proof).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_degree_above_max_degree() {
+        let mut rng = test_rng();
+        let params = FriSha256::setup(16, 4, 32).unwrap();
+        let poly = DensePolynomial::rand(17, &mut rng);
+        assert_eq!(
+            FriSha256::commit(&params, &poly).err(),
+            Some(Error::DegreeTooLarge {
+                degree: 17,
+                max_degree: 16
+            })
+        );
+
+        // a prover committing to it anyway fails the low-degree test
+        let evaluations = poly.evaluate_over_domain_by_ref(params.domain).evals;
+        let commitment = FriCommitment(layer_tree::<Fr, Sha256>(&evaluations).root());
+        let point = Fr::rand(&mut rng);
+        let value = poly.evaluate(&point);
+        let proof = FriSha256::open_evaluations(&params, evaluations, point, value);
+        assert_eq!(
+            FriSha256::verify(&params, &commitment, point, value, &proof),
+            Err(Error::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_open_inside_domain() {
+        let params = FriSha256::setup(8, 2, 4).unwrap();
+        let poly = DensePolynomial::rand(8, &mut test_rng());
+        let point = params.element(0, 3);
+        assert_eq!(
+            FriSha256::open(&params, &poly, point).err(),
+            Some(Error::PointInDomain)
+        );
+    }
+}
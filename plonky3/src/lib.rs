@@ -1,3 +1,4 @@
+pub mod fri;
 pub mod merkletree;
 
 #[cfg(test)]
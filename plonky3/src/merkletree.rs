@@ -6,28 +6,94 @@ pub struct TreeRoot<D: Digest> {
 
 impl<D: Digest> TreeRoot<D> {
     pub fn commit(data: &[impl AsRef<[u8]>]) -> Output<D> {
+        MerkleTree::<D>::new(data).root()
+    }
+
+    /// Checks that `leaf` is the data at position `index` of the tree of
+    /// `depth` levels below the given root. The path must have exactly one
+    /// sibling per level, so that inner nodes cannot pass as leaves.
+    pub fn verify(
+        root: &Output<D>,
+        depth: usize,
+        index: usize,
+        leaf: impl AsRef<[u8]>,
+        path: &MerklePath<D>,
+    ) -> bool {
+        if path.siblings.len() != depth {
+            return false;
+        }
+        let mut index = index;
+        let mut node = D::digest(leaf);
+        for sibling in &path.siblings {
+            let mut hash = D::new();
+            if index & 1 == 0 {
+                hash.update(node.as_ref());
+                hash.update(sibling.as_ref());
+            } else {
+                hash.update(sibling.as_ref());
+                hash.update(node.as_ref());
+            }
+            node = hash.finalize();
+            index >>= 1;
+        }
+        index == 0 && node == *root
+    }
+}
+
+/// Merkle tree keeping every level, so that authentication paths can be
+/// produced after committing.
+pub struct MerkleTree<D: Digest> {
+    levels: Vec<Vec<Output<D>>>,
+}
+
+/// Sibling hashes from the leaf level up to (excluding) the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath<D: Digest> {
+    pub siblings: Vec<Output<D>>,
+}
+
+impl<D: Digest> MerkleTree<D> {
+    pub fn new(data: &[impl AsRef<[u8]>]) -> Self {
         let size = data.len();
         assert!(
             size & (size - 1) == 0,
             "Size of data has to be a power of 2"
         ); // size has to be a power of 2
-        let tree_depth = size.trailing_zeros(); // calculate the log2 of a power of 2
-
-        let mut leafs: Vec<Output<D>> = data.iter().map(|x| D::digest(x)).collect();
-        for tree_level in 0..tree_depth {
-            let step_size = 2 << tree_level;
-            let step_number = size / step_size;
-            for j in 0..step_number {
-                let left_index = j * step_size;
-                let right_index = left_index + step_size / 2;
-                let mut hash = D::new();
-                hash.update(leafs[left_index].as_ref());
-                hash.update(leafs[right_index].as_ref());
-                leafs[left_index] = hash.finalize();
-            }
+
+        let mut levels: Vec<Vec<Output<D>>> = vec![data.iter().map(|x| D::digest(x)).collect()];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| {
+                    let mut hash = D::new();
+                    hash.update(pair[0].as_ref());
+                    hash.update(pair[1].as_ref());
+                    hash.finalize()
+                })
+                .collect();
+            levels.push(level);
         }
+        MerkleTree { levels }
+    }
 
-        leafs[0].clone()
+    pub fn root(&self) -> Output<D> {
+        self.levels.last().unwrap()[0].clone()
+    }
+
+    pub fn num_leaves(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn open(&self, index: usize) -> MerklePath<D> {
+        assert!(index < self.num_leaves(), "Index out of bounds");
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(depth, level)| level[(index >> depth) ^ 1].clone())
+            .collect();
+        MerklePath { siblings }
     }
 }
 
@@ -52,4 +118,46 @@ mod tests {
         let tree_root: Output<Sha256> = TreeRoot::<Sha256>::commit(&leafs);
         assert_eq!(tree_root[..], root_should[..]);
     }
+
+    #[test]
+    fn test_open_and_verify_paths() {
+        let leafs: Vec<[u8; 1]> = (0..8).map(|i| [i]).collect();
+        let tree = MerkleTree::<Sha256>::new(&leafs);
+        let root = TreeRoot::<Sha256>::commit(&leafs);
+        assert_eq!(tree.root(), root);
+
+        for (index, leaf) in leafs.iter().enumerate() {
+            let path = tree.open(index);
+            assert_eq!(path.siblings.len(), 3);
+            assert!(TreeRoot::<Sha256>::verify(&root, 3, index, leaf, &path));
+            assert!(!TreeRoot::<Sha256>::verify(
+                &root,
+                3,
+                index ^ 1,
+                leaf,
+                &path
+            ));
+            assert!(!TreeRoot::<Sha256>::verify(&root, 3, index, [8], &path));
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_truncated_path() {
+        let leafs: Vec<[u8; 1]> = (0..8).map(|i| [i]).collect();
+        let tree = MerkleTree::<Sha256>::new(&leafs);
+        let root = tree.root();
+
+        // the parent of the first two leaves, opened as a leaf one level up
+        let mut inner = Vec::new();
+        inner.extend_from_slice(&Sha256::digest(leafs[0]));
+        inner.extend_from_slice(&Sha256::digest(leafs[1]));
+        let mut path = tree.open(0);
+        path.siblings.remove(0);
+        assert!(TreeRoot::<Sha256>::verify(&root, 2, 0, &inner, &path));
+        assert!(!TreeRoot::<Sha256>::verify(&root, 3, 0, &inner, &path));
+
+        let mut path = tree.open(5);
+        path.siblings.pop();
+        assert!(!TreeRoot::<Sha256>::verify(&root, 3, 5, leafs[5], &path));
+    }
 }